@@ -0,0 +1,75 @@
+//! Builds a struct from the command line with `rust_cmd_arg::parse`.
+//!
+//! Run with `cargo run --example copy -- --source notes.txt --jobs 4 --dry-run`.
+
+use rust_cmd_arg::{
+    CommandLineProcessor, FromCommandLine, ParameterType, ParameterValue, ParseError,
+};
+use std::path::PathBuf;
+use std::process;
+
+#[derive(Debug)]
+struct CopyOptions {
+    source: PathBuf,
+    jobs: i64,
+    dry_run: bool,
+}
+
+impl FromCommandLine for CopyOptions {
+    fn register(processor: &mut CommandLineProcessor) {
+        processor.add_parameter_detail(
+            "source",
+            ParameterType::Path,
+            false,
+            ParameterValue::None,
+            "file to copy",
+            vec!["-s".to_string()],
+        );
+        processor.add_can_empty_parameter(
+            "jobs",
+            ParameterType::Integer,
+            ParameterValue::Integer(1),
+            "number of parallel copies",
+        );
+        processor.add_can_empty_parameter(
+            "dry-run",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "only print what would be copied",
+        );
+        processor.set_version_text("copy 1.0");
+    }
+
+    fn from_processor(processor: &CommandLineProcessor) -> Result<Self, ParseError> {
+        let value = |name: &str| processor.get_parameter(name).unwrap();
+        let jobs = value("jobs")
+            .to_int_value()
+            .map_err(|err| ParseError::for_parameter("jobs", &err))?;
+        if jobs < 1 {
+            return Err(ParseError::for_parameter("jobs", "must be at least 1"));
+        }
+
+        Ok(CopyOptions {
+            source: value("source")
+                .to_path_value()
+                .map_err(|err| ParseError::for_parameter("source", &err))?,
+            jobs,
+            dry_run: *value("dry-run").get_value() == ParameterValue::Flag,
+        })
+    }
+}
+
+fn main() {
+    match rust_cmd_arg::parse::<CopyOptions>() {
+        Ok(options) => println!(
+            "source={} jobs={} dry_run={}",
+            options.source.display(),
+            options.jobs,
+            options.dry_run
+        ),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(2);
+        }
+    }
+}
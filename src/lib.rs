@@ -2,36 +2,49 @@
 //! the code is base on https://github.com/polariseye/cmdpro.
 //!
 //! #examples
-//! ```
+//! ```no_run
 //! // for this example.cmd line is: test --path ./hello.txt --value hello
 //! // and also it can be: test -p ./hello.txt -v hello
 //! // and also it can be: test /path ./hello.txt /value hello
 //! use rust_cmd_arg::{CommandLineProcessor,ParameterType,ParameterValue};
 //! fn main() {
-//! 	// Create a new CommandLineProcessor
+//!     // Create a new CommandLineProcessor
 //!     let mut command_line_processor = CommandLineProcessor::new();
 //!
 //!     // Add Parameters
 //!     let path_param = command_line_processor.add_parameter_detail("path", ParameterType::Path,false,ParameterValue::None,"file path desc" , vec!["-p".to_owned()]);
-//!     let value_param = command_line_processor.add_parameter_detail("value", ParameterType::UInteger,false,ParameterValue::None,"value desc", vec!["-v".to_owned()]);
+//!     let value_param = command_line_processor.add_parameter_detail("value", ParameterType::Integer,false,ParameterValue::None,"value desc", vec!["-v".to_owned()]);
 //!
 //!     // Parse the command line parameters
 //!     command_line_processor.parse_command_line();
 //!
-//! 	// Print the parameter values
+//!     // Print the parameter values
 //!     println!("Path: {:?}", path_param.to_path_value().expect("wrong value for path").to_str().unwrap());
-//!     println!("Value: {}", value_param.to_string_value().expect("wrong value for value"));
+//!     println!("Value: {}", value_param.to_int_value().expect("wrong value for value"));
 //! }
 //! ```
 
-use std::cell::{Ref, RefCell};
-use std::collections::HashMap;
+use std::cell::{Cell, Ref, RefCell};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Function that converts a raw argument into a `ParameterValue`, used by `ParameterType::Custom`.
+pub type ValueParser = Rc<dyn Fn(&str) -> Result<ParameterValue, String>>;
+
+/// Function that receives the rendered help text, see `CommandLineProcessor::set_help_handler`.
+pub type HelpHandler = Box<dyn Fn(&str)>;
+
+/// Function that produces text on demand, e.g. a description that depends on runtime context.
+pub type TextProvider = Box<dyn Fn() -> String>;
 
 /// List of parameter types that can be processed.
-#[derive(Debug)]
 pub enum ParameterType {
     /// Flag parameter.
     Flag,
@@ -50,10 +63,234 @@ pub enum ParameterType {
 
     /// bool value
     Bool,
+
+    /// list of string values. Each occurrence adds its comma separated values, or every
+    /// argument up to the parameter's terminator taken literally.
+    List,
+
+    /// free-form text: every argument that is not an option, joined with spaces into a string value.
+    /// Unknown words written like an option, such as `-x` or `/name`, are still errors. Only one
+    /// parameter of this type can be registered.
+    TrailingText,
+
+    /// value converted by a user supplied parser instead of the built-in parsing.
+    Custom(ValueParser),
+}
+
+impl fmt::Debug for ParameterType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterType::Flag => write!(f, "Flag"),
+            ParameterType::Integer => write!(f, "Integer"),
+            ParameterType::Float => write!(f, "Float"),
+            ParameterType::Path => write!(f, "Path"),
+            ParameterType::String => write!(f, "String"),
+            ParameterType::Bool => write!(f, "Bool"),
+            ParameterType::List => write!(f, "List"),
+            ParameterType::TrailingText => write!(f, "TrailingText"),
+            ParameterType::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Custom types are equal only when they share the same parser.
+impl PartialEq for ParameterType {
+    fn eq(&self, other: &ParameterType) -> bool {
+        match (self, other) {
+            (ParameterType::Custom(a), ParameterType::Custom(b)) => Rc::ptr_eq(a, b),
+            (ParameterType::Custom(_), _) | (_, ParameterType::Custom(_)) => false,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl ParameterType {
+    /// Returns the value placeholder shown in help, `None` for types that take no value.
+    fn value_placeholder(&self) -> Option<&'static str> {
+        match self {
+            ParameterType::Flag => None,
+            ParameterType::Integer => Some("INTEGER"),
+            ParameterType::Float => Some("FLOAT"),
+            ParameterType::Path => Some("PATH"),
+            ParameterType::String => Some("STRING"),
+            ParameterType::Bool => Some("BOOL"),
+            ParameterType::List => Some("VALUE..."),
+            ParameterType::TrailingText => Some("TEXT..."),
+            ParameterType::Custom(_) => Some("VALUE"),
+        }
+    }
+
+    /// Converts a raw string into a value of this type. Command line arguments and
+    /// environment values are all converted here, so they accept the same input and
+    /// report the same errors.
+    pub fn parse_value(&self, raw: &str) -> Result<ParameterValue, String> {
+        self.parse_value_in_radix(raw, None)
+    }
+
+    /// Like `parse_value`, but `ParameterType::Integer` values are read in `radix` if given,
+    /// see `Parameter::radix`.
+    fn parse_value_in_radix(
+        &self,
+        raw: &str,
+        radix: Option<u32>,
+    ) -> Result<ParameterValue, String> {
+        match self {
+            ParameterType::Flag => match raw.parse::<bool>() {
+                Ok(true) => Ok(ParameterValue::Flag),
+                Ok(false) => Ok(ParameterValue::None),
+                Err(err) => Err(format!("Unable to convert {} to flag\n{}", raw, err)),
+            },
+            ParameterType::Integer => parse_integer(raw, radix).map(ParameterValue::Integer),
+            ParameterType::Float => match raw.parse::<f64>() {
+                Ok(val) => Ok(ParameterValue::Float(val)),
+                Err(err) => Err(format!("Unable to convert {} to float\n{}", raw, err)),
+            },
+            ParameterType::Path => Ok(ParameterValue::Path(PathBuf::from(raw))),
+            ParameterType::String | ParameterType::TrailingText => {
+                Ok(ParameterValue::String(raw.to_string()))
+            }
+            ParameterType::Bool => match raw.parse::<bool>() {
+                Ok(val) => Ok(ParameterValue::Bool(val)),
+                Err(err) => Err(format!("Unable to convert {} to bool\n{}", raw, err)),
+            },
+            ParameterType::List => Ok(ParameterValue::List(
+                raw.split(',').map(|item| item.trim().to_string()).collect(),
+            )),
+            ParameterType::Custom(parser) => parser(raw),
+        }
+    }
+}
+
+/// How command line values of a list parameter combine with values from lower layers
+/// (the default value, the environment and `.env` files).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergeStrategy {
+    /// Command line values replace the lower layers.
+    Override,
+
+    /// Command line values are added after the lower layers.
+    Append,
+
+    /// Command line values are added before the lower layers.
+    Prepend,
+}
+
+impl MergeStrategy {
+    fn merge(self, lower: &[String], upper: &[String]) -> Vec<String> {
+        match self {
+            MergeStrategy::Override => upper.to_vec(),
+            MergeStrategy::Append => lower.iter().chain(upper).cloned().collect(),
+            MergeStrategy::Prepend => upper.iter().chain(lower).cloned().collect(),
+        }
+    }
+}
+
+/// Comparison used by a `Relation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// `left < right`
+    Less,
+
+    /// `left <= right`
+    LessOrEqual,
+
+    /// `left == right`
+    Equal,
+
+    /// `left != right`
+    NotEqual,
+
+    /// `left >= right`
+    GreaterOrEqual,
+
+    /// `left > right`
+    Greater,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::NotEqual => ordering != Ordering::Equal,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+/// How long the stages of the last parse took, see `CommandLineProcessor::set_timing_enabled`.
+///
+/// Options are matched and their values converted in one pass over the arguments, so both
+/// are covered by `arguments`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseTiming {
+    /// Reading values from the environment and the loaded `.env` file.
+    pub environment: Duration,
+
+    /// Matching the command line arguments and converting their values, including `@file` reads.
+    pub arguments: Duration,
+
+    /// Applying implications, env interpolation and path handling.
+    pub post_processing: Duration,
+
+    /// Checking required parameters, groups, ordering and relations.
+    pub validation: Duration,
+}
+
+impl ParseTiming {
+    /// Returns the duration of all stages together.
+    pub fn total(&self) -> Duration {
+        self.environment + self.arguments + self.post_processing + self.validation
+    }
+}
+
+/// Returns the time since `lap` and restarts it.
+fn next_lap(lap: &mut Instant) -> Duration {
+    let now = Instant::now();
+    let elapsed = now - *lap;
+    *lap = now;
+    elapsed
+}
+
+/// Required relationship between the values of two numeric parameters, e.g. `min <= max`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    /// Name of the parameter on the left side.
+    pub left: String,
+
+    /// Comparison that must hold.
+    pub comparison: Comparison,
+
+    /// Name of the parameter on the right side.
+    pub right: String,
+}
+
+impl Relation {
+    /// Returns a relation requiring `left <comparison> right`.
+    pub fn new(left: &str, comparison: Comparison, right: &str) -> Relation {
+        Relation {
+            left: left.to_string(),
+            comparison,
+            right: right.to_string(),
+        }
+    }
 }
 
 /// `ParameterType` with its assigned value.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParameterValue {
     /// No value.
     None,
@@ -75,56 +312,74 @@ pub enum ParameterValue {
 
     /// bool value
     Bool(bool),
+
+    /// list of string values
+    List(Vec<String>),
 }
 
 impl ParameterValue {
     pub fn is_none(&self) -> bool {
-        return match self {
-            ParameterValue::None => true,
-            _ => false,
-        };
+        matches!(self, ParameterValue::None)
     }
     pub fn to_int_value(&self) -> Result<i64, String> {
-        return match self {
+        match self {
             ParameterValue::Integer(val) => Ok(*val),
             _ => Err(format!("wrong value type:{:?}", self)),
-        };
+        }
     }
     pub fn to_float_value(&self) -> Result<f64, String> {
-        return match self {
+        match self {
             ParameterValue::Float(val) => Ok(*val),
             _ => Err(format!("wrong value type:{:?}", self)),
-        };
+        }
     }
     pub fn to_path_value(&self) -> Result<PathBuf, String> {
-        return match self {
+        match self {
             ParameterValue::Path(val) => Ok(val.clone()),
             _ => Err(format!("wrong value type:{:?}", self)),
-        };
+        }
     }
     pub fn to_string_value(&self) -> Result<String, String> {
-        return match self {
+        match self {
             ParameterValue::String(val) => Ok(val.to_string()),
             _ => Err(format!("wrong value type:{:?}", self)),
-        };
+        }
     }
     pub fn to_bool_value(&self) -> Result<bool, String> {
-        return match self {
+        match self {
             ParameterValue::Bool(val) => Ok(*val),
             _ => Err(format!("wrong value type:{:?}", self)),
-        };
+        }
+    }
+    pub fn to_list_value(&self) -> Result<Vec<String>, String> {
+        match self {
+            ParameterValue::List(val) => Ok(val.clone()),
+            _ => Err(format!("wrong value type:{:?}", self)),
+        }
+    }
+
+    /// Compares two `Integer` or `Float` values; `None` for other values or NaN.
+    fn compare_numeric(&self, other: &ParameterValue) -> Option<Ordering> {
+        match (self, other) {
+            (ParameterValue::Integer(a), ParameterValue::Integer(b)) => Some(a.cmp(b)),
+            (ParameterValue::Integer(a), ParameterValue::Float(b)) => (*a as f64).partial_cmp(b),
+            (ParameterValue::Float(a), ParameterValue::Integer(b)) => a.partial_cmp(&(*b as f64)),
+            (ParameterValue::Float(a), ParameterValue::Float(b)) => a.partial_cmp(b),
+            _ => None,
+        }
     }
 
     pub fn to_help_string(&self) -> String {
-        return match self {
+        match self {
             ParameterValue::None => "".to_string(),
             ParameterValue::Flag => true.to_string(),
             ParameterValue::Integer(val) => format!("{}", val),
             ParameterValue::Float(val) => format!("{}", val),
-            ParameterValue::Path(val) => format!("{}", val.to_str().unwrap()),
-            ParameterValue::String(val) => format!("{}", val),
+            ParameterValue::Path(val) => val.to_str().unwrap().to_string(),
+            ParameterValue::String(val) => val.to_string(),
             ParameterValue::Bool(val) => format!("{}", val),
-        };
+            ParameterValue::List(val) => val.join(","),
+        }
     }
 }
 
@@ -132,15 +387,44 @@ pub struct Parameter {
     parameter_name: String,
     parameter_type: ParameterType,
     allow_empty: bool,
-    aliases: Vec<String>,
+    aliases: RefCell<Vec<String>>,
     description: String,
     default_value: ParameterValue,
     value: RefCell<ParameterValue>,
+    terminator: RefCell<Option<String>>,
+    radix: Cell<Option<u32>>,
+    merge_strategy: Cell<MergeStrategy>,
+    dedup: Cell<bool>,
+    sorted: Cell<bool>,
+    was_set: Cell<bool>,
+    implied: Cell<bool>,
+    occurrences: Cell<usize>,
+    experimental: Cell<bool>,
+    sensitive: Cell<bool>,
+    env_presence: RefCell<Option<String>>,
+    position: Cell<Option<usize>>,
+    description_provider: RefCell<Option<TextProvider>>,
 }
 
 impl Parameter {
-    pub fn get_value(&self) -> Ref<ParameterValue> {
-        return self.value.borrow();
+    pub fn get_value(&self) -> Ref<'_, ParameterValue> {
+        self.value.borrow()
+    }
+
+    /// Returns true if the value was given on the command line or in the environment,
+    /// rather than coming from the default.
+    pub fn was_set(&self) -> bool {
+        self.was_set.get()
+    }
+
+    /// Returns how many times the parameter appeared on the command line, under any alias.
+    pub fn occurrences(&self) -> usize {
+        self.occurrences.get()
+    }
+
+    /// Returns the index in the argument list where the parameter first appeared.
+    pub fn position(&self) -> Option<usize> {
+        self.position.get()
     }
 
     pub fn to_int_value(&self) -> Result<i64, String> {
@@ -203,416 +487,4247 @@ impl Parameter {
 
         val.to_bool_value()
     }
-}
-
-/// Command Line Processor
-pub struct CommandLineProcessor {
-    parameters: HashMap<String, Rc<Parameter>>,
-    version_text: Option<String>,
-    abort_flag: bool,
-}
+    pub fn to_list_value(&self) -> Result<Vec<String>, String> {
+        let val = self.value.borrow();
+        if val.is_none() {
+            if self.allow_empty {
+                return Ok(vec![]);
+            }
 
-impl CommandLineProcessor {
-    /// Returns a new `CommandLineProcessor`.
-    pub fn new() -> CommandLineProcessor {
-        CommandLineProcessor {
-            parameters: HashMap::new(),
-            version_text: None,
-            abort_flag: false,
+            return Err(format!("{} is None Value", &self.parameter_name));
         }
+
+        val.to_list_value()
     }
 
-    /// Add a parameter to be parsed.
-    pub fn add_parameter_detail(
-        &mut self,
-        parameter_name: &str,
-        parameter_type: ParameterType,
-        allow_empty: bool,
-        default_value: ParameterValue,
-        description: &str,
-        mut aliases: Vec<String>,
-    ) -> Rc<Parameter> {
-        let alias1 = "/".to_string() + parameter_name;
-        let alias2 = "--".to_string() + parameter_name;
-        if aliases.iter().any(|item| item == &alias1) == false {
-            aliases.push(alias1)
-        }
-        if aliases.iter().any(|item| item == &alias2) == false {
-            aliases.push(alias2)
+    /// Marks the parameter as experimental: using it fails parsing unless the flag registered
+    /// with `CommandLineProcessor::set_experimental_gate` is also set.
+    pub fn experimental(&self, experimental: bool) -> &Parameter {
+        self.experimental.set(experimental);
+        self
+    }
+
+    /// Computes the description each time the help text is rendered, e.g. to show
+    /// `defaults to <number of CPUs>`. Replaces the static description.
+    pub fn description_provider(&self, description_provider: TextProvider) -> &Parameter {
+        *self.description_provider.borrow_mut() = Some(description_provider);
+        self
+    }
+
+    /// Returns the description to show in the help text.
+    fn help_description(&self) -> String {
+        match &*self.description_provider.borrow() {
+            Some(description_provider) => description_provider(),
+            None => self.description.to_string(),
         }
+    }
 
-        let parameter = Rc::new(Parameter {
-            parameter_name: parameter_name.to_owned(),
-            parameter_type,
-            aliases,
-            allow_empty,
-            description: description.to_string(),
-            default_value: default_value.clone(),
-            value: RefCell::new(default_value),
-        });
+    /// Marks the parameter as holding a secret, so it is left out of `export_env`.
+    pub fn sensitive(&self, sensitive: bool) -> &Parameter {
+        self.sensitive.set(sensitive);
+        self
+    }
 
-        self.parameters
-            .insert(parameter_name.to_owned(), parameter.clone());
+    /// Sets a `ParameterType::Flag` parameter when the environment variable `env_name` is
+    /// present, whatever its value, e.g. `MYAPP_DEBUG=1` or `MYAPP_DEBUG=no` both enable it.
+    /// An empty value counts as unset. Works without an env prefix and is overridden by the
+    /// command line like other environment values.
+    pub fn env_presence(&self, env_name: &str) -> &Parameter {
+        *self.env_presence.borrow_mut() = Some(env_name.to_string());
+        self
+    }
 
-        parameter
+    /// Sets how command line values of a `ParameterType::List` parameter combine with the
+    /// default and environment values. Defaults to `MergeStrategy::Override`.
+    ///
+    /// Scalar parameters only support `MergeStrategy::Override`; the setting is ignored for them.
+    pub fn merge_strategy(&self, merge_strategy: MergeStrategy) -> &Parameter {
+        self.merge_strategy.set(merge_strategy);
+        self
     }
 
-    /// 添加参数
-    pub fn add_simple_parameter(
-        &mut self,
-        parameter_name: &str,
-        parameter_type: ParameterType,
-        description: &str,
-    ) -> Rc<Parameter> {
-        self.add_parameter_detail(
-            parameter_name,
-            parameter_type,
-            false,
-            ParameterValue::None,
-            description,
-            vec![],
-        )
+    /// Drops repeated values of a `ParameterType::List` parameter, keeping the first occurrence
+    /// of each. Applied after the values from all layers have been combined. A default value
+    /// that no layer replaces or extends is used as given.
+    pub fn dedup(&self, dedup: bool) -> &Parameter {
+        self.dedup.set(dedup);
+        self
     }
 
-    /// 添加参数
-    pub fn add_can_empty_parameter(
-        &mut self,
-        parameter_name: &str,
-        parameter_type: ParameterType,
-        default_value: ParameterValue,
-        description: &str,
-    ) -> Rc<Parameter> {
-        self.add_parameter_detail(
-            parameter_name,
-            parameter_type,
-            true,
-            default_value,
-            description,
-            vec![],
-        )
+    /// Sorts the values of a `ParameterType::List` parameter. Applied after the values from all
+    /// layers have been combined; without it values keep the order they were given in. A
+    /// default value that no layer replaces or extends is used as given.
+    pub fn sorted(&self, sorted: bool) -> &Parameter {
+        self.sorted.set(sorted);
+        self
     }
-    /// Parses the program's command line parameters.
+
+    /// Applies the `dedup` and `sorted` settings to collected list values.
+    fn finish_list(&self, mut values: Vec<String>) -> Vec<String> {
+        if self.sorted.get() {
+            values.sort();
+        }
+        if self.dedup.get() {
+            let mut seen = HashSet::new();
+            values.retain(|value| seen.insert(value.clone()));
+        }
+        values
+    }
+
+    /// Parses `ParameterType::Integer` values in a fixed radix (2 to 36) instead of
+    /// detecting it from the `0x`/`0o`/`0b` prefix.
     ///
     /// # Panics
-    /// Panics if the parameter type requires a value and no value is provided.
-    /// It will also panic if the parameter is the wrong type.
-    pub fn parse_command_line(&mut self) {
-        let mut iter = env::args();
-        iter.next(); // Skip executable name
-
-        loop {
-            match iter.next() {
-                Some(argument) => match argument.as_ref() {
-                    "--help" => {
-                        self.print_help_text();
-                        self.abort_flag = true;
-                        break;
-                    }
-                    "--h" => {
-                        self.print_help_text();
-                        self.abort_flag = true;
-                        break;
-                    }
-                    "--version" => {
-                        self.print_version_text();
-                        self.abort_flag = true;
-                        break;
-                    }
-                    "--v" => {
-                        self.print_version_text();
-                        self.abort_flag = true;
-                        break;
-                    }
-                    arg => {
-                        let mut parameter_exists = false;
+    /// Panics if `radix` is not in the range 2 to 36.
+    pub fn radix(&self, radix: u32) -> &Parameter {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        self.radix.set(Some(radix));
+        self
+    }
 
-                        for (name, parameter) in self.parameters.iter_mut() {
-                            if parameter.aliases.iter().any(|x| x == arg) {
-                                parameter_exists = true;
+    /// Returns the default value as shown in the help text, in the parameter's radix if it has one.
+    fn default_help_string(&self) -> String {
+        match (&self.default_value, self.radix.get()) {
+            (ParameterValue::Integer(val), Some(radix)) => format_integer(*val, radix),
+            (default_value, _) => default_value.to_help_string(),
+        }
+    }
 
-                                match parameter.parameter_type {
-                                    ParameterType::Flag => {
-                                        *parameter.value.borrow_mut() = ParameterValue::Flag
-                                    }
-                                    ParameterType::Integer => match iter.next() {
-                                        Some(val) => {
-                                            if val.is_empty() {
-                                                if parameter.allow_empty == false {
-                                                    println!(
-                                                        "No value passed for parameter {}",
-                                                        name
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                            }
+    /// Converts a raw string into a value of this parameter's type.
+    fn parse_value(&self, raw: &str) -> Result<ParameterValue, String> {
+        match self
+            .parameter_type
+            .parse_value_in_radix(raw, self.radix.get())?
+        {
+            ParameterValue::List(values) => Ok(ParameterValue::List(self.finish_list(values))),
+            value => Ok(value),
+        }
+    }
 
-                                            match val.parse::<i64>() {
-                                                Ok(val) => {
-                                                    *parameter.value.borrow_mut() =
-                                                        ParameterValue::Integer(val)
-                                                }
-                                                Err(err) => {
-                                                    println!(
-                                                        "Unable to convert parameter {} to integer\n{}",
-                                                        name, err
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            if parameter.allow_empty == false {
-                                                println!("No value passed for parameter {}", name);
-                                                self.abort_flag = true;
-                                                break;
-                                            }
-                                        }
-                                    },
-                                    ParameterType::Float => match iter.next() {
-                                        Some(val) => {
-                                            if val.is_empty() {
-                                                if parameter.allow_empty == false {
-                                                    println!(
-                                                        "No value passed for parameter {}",
-                                                        name
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                                continue;
-                                            }
+    /// Drops everything a parse assigned, going back to the default value.
+    fn reset(&self) {
+        *self.value.borrow_mut() = self.default_value.clone();
+        self.was_set.set(false);
+        self.implied.set(false);
+        self.occurrences.set(0);
+        self.position.set(None);
+    }
 
-                                            match val.parse::<f64>() {
-                                                Ok(val) => {
-                                                    *parameter.value.borrow_mut() =
-                                                        ParameterValue::Float(val)
-                                                }
-                                                Err(err) => {
-                                                    println!(
-                                                        "Unable to convert parameter {} to float\n{}",
-                                                        name, err
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            if parameter.allow_empty == false {
-                                                panic!("No value passed for parameter {}", name)
-                                            }
-                                        }
-                                    },
-                                    ParameterType::String => match iter.next() {
-                                        Some(val) => {
-                                            if val.is_empty() {
-                                                if parameter.allow_empty == false {
-                                                    println!(
-                                                        "No value passed for parameter {}",
-                                                        name
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                                continue;
-                                            }
+    /// Returns true if the value was given on the command line or in the environment, or
+    /// turned on by an implication, and is not `ParameterValue::None`, e.g. not a flag set
+    /// to `false`.
+    fn has_set_value(&self) -> bool {
+        (self.was_set() || self.implied.get()) && !self.value.borrow().is_none()
+    }
 
-                                            *parameter.value.borrow_mut() =
-                                                ParameterValue::String(val)
-                                        }
-                                        None => {
-                                            if parameter.allow_empty == false {
-                                                println!("No value passed for parameter {}", name);
-                                                self.abort_flag = true;
-                                                break;
-                                            }
-                                        }
-                                    },
-                                    ParameterType::Bool => match iter.next() {
-                                        Some(val) => {
-                                            if val.is_empty() {
-                                                if parameter.allow_empty == false {
-                                                    println!(
-                                                        "No value passed for parameter {}",
-                                                        name
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                                continue;
-                                            }
+    /// Returns true if the parameter is matched by `alias`.
+    fn has_alias(&self, alias: &str) -> bool {
+        self.aliases.borrow().iter().any(|x| x == alias)
+    }
 
-                                            match val.parse::<bool>() {
-                                                Ok(val) => {
-                                                    *parameter.value.borrow_mut() =
-                                                        ParameterValue::Bool(val)
-                                                }
-                                                Err(err) => {
-                                                    println!(
-                                                        "Unable to convert parameter {} to bool\n{}",
-                                                        name, err
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            if parameter.allow_empty == false {
-                                                println!("No value passed for parameter {}", name);
-                                                self.abort_flag = true;
-                                                break;
-                                            }
-                                        }
-                                    },
-                                    ParameterType::Path => match iter.next() {
-                                        Some(val) => {
-                                            if val.is_empty() {
-                                                if parameter.allow_empty == false {
-                                                    println!(
-                                                        "No value passed for parameter {}",
-                                                        name
-                                                    );
-                                                    self.abort_flag = true;
-                                                    break;
-                                                }
-                                                continue;
-                                            }
+    /// Returns true if both parameters have the same definition, ignoring their values.
+    fn definition_eq(&self, other: &Parameter) -> bool {
+        self.parameter_name == other.parameter_name
+            && self.parameter_type == other.parameter_type
+            && self.allow_empty == other.allow_empty
+            && *self.aliases.borrow() == *other.aliases.borrow()
+            && self.description == other.description
+            && self.default_value == other.default_value
+            && *self.terminator.borrow() == *other.terminator.borrow()
+            && self.radix.get() == other.radix.get()
+            && self.merge_strategy.get() == other.merge_strategy.get()
+            && self.dedup.get() == other.dedup.get()
+            && self.sorted.get() == other.sorted.get()
+            && self.experimental.get() == other.experimental.get()
+            && self.sensitive.get() == other.sensitive.get()
+            && *self.env_presence.borrow() == *other.env_presence.borrow()
+    }
 
-                                            let mut path = PathBuf::new();
-                                            path.push(val);
-                                            *parameter.value.borrow_mut() =
-                                                ParameterValue::Path(path);
-                                        }
-                                        None => {
-                                            if parameter.allow_empty == false {
-                                                println!("No value passed for parameter {}", name);
-                                                self.abort_flag = true;
-                                                break;
-                                            }
-                                        }
-                                    },
-                                }
-                            }
-                        }
+    /// Makes a `ParameterType::List` parameter collect every following argument
+    /// until `terminator`, e.g. `--files a b c --end`.
+    ///
+    /// Without a terminator each occurrence of the parameter adds a single value.
+    pub fn terminator(&self, terminator: &str) -> &Parameter {
+        *self.terminator.borrow_mut() = Some(terminator.to_string());
+        self
+    }
+}
 
-                        if !parameter_exists {
-                            println!("Unknown parameter: {}", arg);
-                            self.abort_flag = true;
-                            break;
-                        }
-                    }
-                },
-                None => break,
-            }
-        }
+/// Category of a `ParseError`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    /// An argument did not match any parameter.
+    UnknownParameter,
 
-        if self.abort_flag {
-            self.print_help_text();
-            std::process::exit(-1);
+    /// An option that takes a value was not followed by one.
+    MissingValue,
+
+    /// A value could not be converted to the parameter type.
+    InvalidValue,
+
+    /// A required parameter was not set.
+    MissingRequired,
+
+    /// A constraint between parameters (groups, order, experimental gate) was violated.
+    Constraint,
+
+    /// Any other error.
+    Other,
+}
+
+impl ParseErrorKind {
+    /// Returns the snake_case name used in machine-readable reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            ParseErrorKind::UnknownParameter => "unknown_parameter",
+            ParseErrorKind::MissingValue => "missing_value",
+            ParseErrorKind::InvalidValue => "invalid_value",
+            ParseErrorKind::MissingRequired => "missing_required",
+            ParseErrorKind::Constraint => "constraint",
+            ParseErrorKind::Other => "other",
         }
-        if self.check_if_parse_all_arg() == false {
-            self.print_help_text();
-            std::process::exit(-2);
+    }
+}
+
+/// Error produced when the parsed command line can not be used as requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Category of the error.
+    pub kind: ParseErrorKind,
+
+    /// Name of the parameter the error relates to, if any.
+    pub parameter: Option<String>,
+
+    /// The offending command line argument, if any.
+    pub argument: Option<String>,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ParseError {
+    /// Returns a new error that is not tied to a parameter.
+    pub fn new(message: &str) -> ParseError {
+        ParseError::with_kind(ParseErrorKind::Other, None, None, message.to_string())
+    }
+
+    /// Returns a new error for the given parameter.
+    pub fn for_parameter(parameter_name: &str, message: &str) -> ParseError {
+        ParseError::with_kind(
+            ParseErrorKind::Other,
+            Some(parameter_name),
+            None,
+            message.to_string(),
+        )
+    }
+
+    /// Returns a new error with all fields given.
+    pub fn with_kind(
+        kind: ParseErrorKind,
+        parameter_name: Option<&str>,
+        argument: Option<&str>,
+        message: String,
+    ) -> ParseError {
+        ParseError {
+            kind,
+            parameter: parameter_name.map(|name| name.to_string()),
+            argument: argument.map(|argument| argument.to_string()),
+            message,
         }
     }
 
-    fn check_if_parse_all_arg(&mut self) -> bool {
-        let mut if_parse_all_arg = true;
-        for item in self.parameters.values() {
-            if item.allow_empty {
-                continue;
-            }
+    /// Returns the error as a JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"kind\":\"{}\",\"parameter\":{},\"argument\":{},\"message\":{}}}",
+            self.kind.name(),
+            json_string_or_null(self.parameter.as_deref()),
+            json_string_or_null(self.argument.as_deref()),
+            json_string(&self.message)
+        )
+    }
+}
 
-            if item.value.borrow().is_none() {
-                println!("cmd arg {} is no set", &item.parameter_name);
-                self.abort_flag = true;
-                if_parse_all_arg = false;
-            }
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter {
+            Some(parameter_name) => write!(f, "{}: {}", parameter_name, self.message),
+            None => write!(f, "{}", self.message),
         }
+    }
+}
+
+impl std::error::Error for ParseError {}
 
-        if_parse_all_arg
+impl From<String> for ParseError {
+    fn from(message: String) -> ParseError {
+        ParseError::with_kind(ParseErrorKind::Other, None, None, message)
     }
+}
 
-    /// Print the default help text
-    fn print_help_text(&self) {
-        println!(
-            "USAGE \r\n\t{} [OPTIONS]\r\n",
-            std::env::current_exe().unwrap().to_str().unwrap()
-        );
-        println!("OPTIONS");
+/// How parse errors are reported when `parse_command_line` aborts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorReportFormat {
+    /// The errors as one block, see `format_error_block`, followed by the help text.
+    Text,
 
-        let mut param_str_list: Vec<Vec<String>> = vec![];
-        param_str_list.push(vec![
-            "arg".to_string(),
-            "IsCanEmpty".to_string(),
-            "DefaultValue".to_string(),
-            "Description".to_string(),
-        ]);
-        for item in self.parameters.values() {
-            // name[alias1,alias2] can empty default value description
+    /// A single JSON array with one object per error, without the help text.
+    Json,
+}
 
-            let arg_name = format!("{}", item.aliases.join(","));
-            let mut can_empty = "false";
-            if item.allow_empty {
-                can_empty = "true";
+/// Renders `errors` as a single block under one `error:` header.
+///
+/// A single error is shown as `error: <message>`. Several errors are numbered, with the
+/// continuation lines of multi-line messages indented under their number:
+///
+/// ```text
+/// error: 2 problems found
+///   1. No value passed for parameter path
+///   2. at least one of --a, --b is required
+/// ```
+pub fn format_error_block(errors: &[ParseError]) -> String {
+    match errors {
+        [] => String::new(),
+        [error] => format!("error: {}", error.message),
+        _ => {
+            let mut block = format!("error: {} problems found", errors.len());
+            let number_width = errors.len().to_string().len();
+            for (index, error) in errors.iter().enumerate() {
+                let indent = " ".repeat(number_width + 4);
+                for (line_index, line) in error.message.lines().enumerate() {
+                    if line_index == 0 {
+                        block.push_str(&format!(
+                            "\n  {:>width$}. {}",
+                            index + 1,
+                            line,
+                            width = number_width
+                        ));
+                    } else {
+                        block.push_str(&format!("\n{}{}", indent, line));
+                    }
+                }
             }
+            block
+        }
+    }
+}
 
-            let default_value = item.default_value.to_help_string();
-            param_str_list.push(vec![
-                arg_name,
-                can_empty.to_string(),
-                default_value.to_string(),
-                item.description.to_string(),
-            ]);
+/// Quotes and escapes `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
         }
+    }
+    result.push('"');
 
-        // calculate width
-        let mut col_max_width: [usize; 4] = [0, 0, 0, 0];
-        for arg_item in &param_str_list {
-            for col_index in 0..arg_item.len() {
-                let tmp_len = arg_item[col_index].len();
-                if tmp_len > col_max_width[col_index] {
-                    col_max_width[col_index] = tmp_len;
+    result
+}
+
+/// Returns `text` as a JSON string, or `null` if there is none.
+fn json_string_or_null(text: Option<&str>) -> String {
+    match text {
+        Some(text) => json_string(text),
+        None => "null".to_string(),
+    }
+}
+
+/// Types that can be built from a parsed `CommandLineProcessor`.
+///
+/// Implementations pull their fields through the processor getters, for example
+/// `processor.get_parameter("path")`, after `parse_command_line` has run.
+pub trait FromCommandLine: Sized {
+    /// Adds the parameters the type is built from, and any other processor settings it needs.
+    /// Used by `parse`.
+    fn register(processor: &mut CommandLineProcessor);
+
+    /// Builds the type from the parsed parameter values, see `CommandLineProcessor::extract`.
+    fn from_processor(processor: &CommandLineProcessor) -> Result<Self, ParseError>;
+}
+
+/// Builds a processor with `CommandLineProcessor::with_defaults`, lets `T` register its
+/// parameters, parses the program's command line and returns the populated `T`.
+///
+/// Like `parse_command_line` this prints the help text and exits on `--help`, `--version`
+/// and invalid arguments, so those never reach the caller. The `Err` case only comes from
+/// `T::from_processor`. See `examples/copy.rs`.
+pub fn parse<T: FromCommandLine>() -> Result<T, ParseError> {
+    let mut processor = CommandLineProcessor::with_defaults();
+    T::register(&mut processor);
+    processor.parse_command_line();
+    processor.extract()
+}
+
+/// Returns true if `raw` starts with a drive such as `C:`, followed by a separator or nothing.
+/// Other names with `:` as their second character, e.g. `x:y`, are relative.
+fn has_drive_prefix(raw: &str) -> bool {
+    let mut chars = raw.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(drive), Some(':'), None | Some('/') | Some('\\')) if drive.is_ascii_alphabetic()
+    )
+}
+
+/// Returns true if an unrecognized `argument` is written like an option, so it is reported
+/// as unknown instead of becoming trailing text: `-x`, `--name`, or `/name` with no further
+/// `/` before an `=`. Paths such as `/tmp/x` are still taken as words.
+fn looks_like_option(argument: &str) -> bool {
+    match argument.strip_prefix('/') {
+        Some(rest) => !rest.split('=').next().unwrap_or_default().contains('/'),
+        None => argument.starts_with('-'),
+    }
+}
+
+/// Parses an integer, detecting `0x`, `0o` and `0b` prefixes unless a fixed radix is given.
+///
+/// With a fixed radix the matching prefix is still accepted, e.g. `0xFF` and `FF` for radix 16.
+fn parse_integer(raw: &str, radix: Option<u32>) -> Result<i64, String> {
+    let (sign, unsigned) = match raw.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", raw.strip_prefix('+').unwrap_or(raw)),
+    };
+
+    let prefix_radix = match unsigned.get(..2) {
+        Some("0x") | Some("0X") => Some(16),
+        Some("0o") | Some("0O") => Some(8),
+        Some("0b") | Some("0B") => Some(2),
+        _ => None,
+    };
+
+    let (radix, digits) = match (radix, prefix_radix) {
+        (Some(radix), Some(prefix_radix)) if radix == prefix_radix => (radix, &unsigned[2..]),
+        (Some(radix), _) => (radix, unsigned),
+        (None, Some(prefix_radix)) => (prefix_radix, &unsigned[2..]),
+        (None, None) => (10, unsigned),
+    };
+
+    let base_name = match radix {
+        2 => "binary".to_string(),
+        8 => "octal".to_string(),
+        10 => "decimal".to_string(),
+        16 => "hexadecimal".to_string(),
+        _ => format!("base {}", radix),
+    };
+
+    // the sign was consumed above; from_str_radix would accept a second one, e.g. `+-5`
+    if digits.starts_with(['+', '-']) {
+        return Err(format!(
+            "invalid {} integer {}: repeated sign",
+            base_name, raw
+        ));
+    }
+
+    i64::from_str_radix(&format!("{}{}", sign, digits), radix)
+        .map_err(|err| format!("invalid {} integer {}: {}", base_name, raw, err))
+}
+
+/// Replaces an `@<path>` value with the trimmed contents of the file at `<path>`.
+///
+/// A leading `@@` escapes a literal `@`, so `@@name` yields `@name`.
+fn read_value_file(val: String) -> Result<String, String> {
+    if let Some(escaped) = val.strip_prefix("@@") {
+        return Ok(format!("@{}", escaped));
+    }
+
+    match val.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)
+            .map(|content| content.trim().to_string())
+            .map_err(|err| format!("Unable to read value file {}\n{}", path, err)),
+        None => Ok(val),
+    }
+}
+
+/// Expands `$VAR` and `${VAR}` from the environment; `$$` yields a literal `$`.
+///
+/// Undefined variables expand to an empty string. A `$` that does not start a variable
+/// name, and an unterminated `${`, are kept as written.
+fn interpolate_env(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        if let Some(after) = rest.strip_prefix('$') {
+            result.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            match after.find('}') {
+                Some(end) => {
+                    result.push_str(&env::var(&after[..end]).unwrap_or_default());
+                    rest = &after[end + 1..];
                 }
+                None => result.push('$'),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                result.push('$');
+            } else {
+                result.push_str(&env::var(&rest[..end]).unwrap_or_default());
+                rest = &rest[end..];
             }
         }
+    }
+    result.push_str(rest);
 
-        // print
-        for arg_item in &param_str_list {
-            println!("\t{name:name_width$}\t{can_empty:can_empty_width$}\t{default_value:default_value_width$}\t{description:description_width$}",
-                     name=arg_item[0],name_width=col_max_width[0]
-                     ,can_empty=arg_item[1],can_empty_width=col_max_width[1]
-                     ,default_value=arg_item[2],default_value_width=col_max_width[2]
-                     ,description=arg_item[3],description_width=col_max_width[3])
+    result
+}
+
+/// Returns the environment variable name `PREFIX_NAME` for a parameter.
+///
+/// Every character other than an ASCII letter or digit becomes `_`, and a leading digit gets
+/// a `_` in front, so the result is always a valid shell variable name.
+fn env_var_name(prefix: &str, parameter_name: &str) -> String {
+    let name = if prefix.is_empty() {
+        parameter_name.to_string()
+    } else {
+        format!("{}_{}", prefix, parameter_name)
+    };
+
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Wraps `text` in single quotes for POSIX shells.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Formats parameter names as `--name` options; `last_separator` goes before the last name.
+fn join_option_names<S: AsRef<str>>(names: &[S], last_separator: &str) -> String {
+    let options: Vec<String> = names
+        .iter()
+        .map(|name| format!("--{}", name.as_ref()))
+        .collect();
+
+    match options.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{}{}{}", rest.join(", "), last_separator, last)
         }
+        _ => options.join(""),
     }
+}
 
-    /// Sets the text to print when the `--version` parameter is used.
-    pub fn set_version_text(&mut self, version_text: &str) {
-        self.version_text = Some(version_text.to_owned());
+/// Formats an integer in `radix` the way it is typed: with a `0x`, `0o` or `0b` prefix
+/// for radix 16, 8 and 2, and as plain digits otherwise.
+fn format_integer(val: i64, radix: u32) -> String {
+    let prefix = match radix {
+        16 => "0x",
+        8 => "0o",
+        2 => "0b",
+        _ => "",
+    };
+
+    let mut magnitude = val.unsigned_abs();
+    let mut digits = vec![];
+    loop {
+        let digit = std::char::from_digit((magnitude % radix as u64) as u32, radix).unwrap();
+        digits.push(digit.to_ascii_uppercase());
+        magnitude /= radix as u64;
+        if magnitude == 0 {
+            break;
+        }
     }
+    digits.reverse();
 
-    /// Prints the version text. Prints a default message if the version text is not set.
-    fn print_version_text(&self) {
-        match &self.version_text {
-            Some(version_text) => println!("{}", version_text),
-            None => println!("No version text has been set."),
+    let sign = if val < 0 { "-" } else { "" };
+    format!(
+        "{}{}{}",
+        sign,
+        prefix,
+        digits.into_iter().collect::<String>()
+    )
+}
+
+/// Splits text into lines of at most `width` characters, breaking on whitespace.
+///
+/// Words longer than `width` are kept whole on their own line.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(line);
+            line = String::new();
         }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
     }
+    lines.push(line);
 
-    /// Returns the `ParameterValue` for the specified parameter. Returns `ParameterValue::None` if the parameter doesn't exist.
-    pub fn get_parameter_value(&self, parameter_name: &str) -> Option<Ref<ParameterValue>> {
-        match self.parameters.get(parameter_name) {
-            Some(parameter) => Some(parameter.value.borrow()),
-            None => None,
+    lines
+}
+
+/// Rust types that map onto a `ParameterType`, used by typed registration.
+pub trait TypedValue: Sized {
+    /// The `ParameterType` the value is parsed as.
+    fn parameter_type() -> ParameterType;
+
+    /// Extract the typed value from a parsed `ParameterValue`.
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String>;
+}
+
+impl TypedValue for i64 {
+    fn parameter_type() -> ParameterType {
+        ParameterType::Integer
+    }
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String> {
+        value.to_int_value()
+    }
+}
+
+impl TypedValue for f64 {
+    fn parameter_type() -> ParameterType {
+        ParameterType::Float
+    }
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String> {
+        value.to_float_value()
+    }
+}
+
+impl TypedValue for PathBuf {
+    fn parameter_type() -> ParameterType {
+        ParameterType::Path
+    }
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String> {
+        value.to_path_value()
+    }
+}
+
+impl TypedValue for String {
+    fn parameter_type() -> ParameterType {
+        ParameterType::String
+    }
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String> {
+        value.to_string_value()
+    }
+}
+
+impl TypedValue for bool {
+    fn parameter_type() -> ParameterType {
+        ParameterType::Bool
+    }
+    fn from_parameter_value(value: &ParameterValue) -> Result<Self, String> {
+        value.to_bool_value()
+    }
+}
+
+/// Handle of a required parameter registered with `add_required_typed`.
+///
+/// A successful `parse_command_line` guarantees every required parameter is set,
+/// so the getter returns the value directly instead of a `Result`.
+pub struct RequiredParameter<T: TypedValue> {
+    parameter: Rc<Parameter>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TypedValue> RequiredParameter<T> {
+    /// Returns the parsed value.
+    ///
+    /// # Panics
+    /// Panics if the parameter has no value, i.e. before the command line has been
+    /// parsed or after the value was reset with `CommandLineProcessor::clear_value`.
+    pub fn get(&self) -> T {
+        match T::from_parameter_value(&self.parameter.get_value()) {
+            Ok(val) => val,
+            Err(err) => panic!(
+                "required parameter {} has no value: {}",
+                &self.parameter.parameter_name, err
+            ),
         }
     }
 
-    /// Returns true if the `CommandLineProcessor` reads `--help` or `--version` in the parameter list.
-    pub fn abort_flag(&self) -> bool {
-        self.abort_flag
+    /// Returns the underlying untyped parameter.
+    pub fn parameter(&self) -> &Rc<Parameter> {
+        &self.parameter
+    }
+}
+
+/// Command Line Processor
+pub struct CommandLineProcessor {
+    parameters: HashMap<String, Rc<Parameter>>,
+    version_text: Option<String>,
+    version_provider: Option<TextProvider>,
+    env_prefix: Option<String>,
+    help_width: usize,
+    table_help: bool,
+    sorted_help: bool,
+    help_aliases: Vec<String>,
+    version_aliases: Vec<String>,
+    help_handler: Option<HelpHandler>,
+    path_base: Option<PathBuf>,
+    path_separator: Option<char>,
+    env_interpolation: bool,
+    dotenv_values: HashMap<String, String>,
+    errors: Vec<ParseError>,
+    error_report_format: ErrorReportFormat,
+    exactly_one_groups: Vec<Vec<String>>,
+    at_least_one_groups: Vec<Vec<String>>,
+    experimental_gate: Option<String>,
+    order_constraints: Vec<(String, String)>,
+    implications: Vec<(String, Vec<String>)>,
+    relations: Vec<Relation>,
+    timing_enabled: bool,
+    timing: ParseTiming,
+    help_requested: bool,
+    version_requested: bool,
+    abort_flag: bool,
+}
+
+/// Successful result of `CommandLineProcessor::try_parse`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseOutcome {
+    /// The arguments were parsed and validated.
+    Parsed,
+
+    /// A help alias was given; parsing stopped there.
+    HelpRequested,
+
+    /// A version alias was given; parsing stopped there. Holds the version text.
+    VersionRequested(String),
+}
+
+/// Output of an aborted `parse_command_line`, split by destination.
+struct ExitReport {
+    stdout: String,
+    stderr: String,
+    /// Whether the help text follows on stderr, or goes to the help handler.
+    help: bool,
+    code: i32,
+}
+
+impl Default for CommandLineProcessor {
+    fn default() -> CommandLineProcessor {
+        CommandLineProcessor::new()
+    }
+}
+
+impl CommandLineProcessor {
+    /// Returns a new `CommandLineProcessor`.
+    pub fn new() -> CommandLineProcessor {
+        CommandLineProcessor {
+            parameters: HashMap::new(),
+            version_text: None,
+            version_provider: None,
+            env_prefix: None,
+            help_width: 80,
+            table_help: false,
+            sorted_help: false,
+            help_aliases: vec!["--help".to_string(), "--h".to_string()],
+            version_aliases: vec!["--version".to_string(), "--v".to_string()],
+            help_handler: None,
+            path_base: None,
+            path_separator: None,
+            env_interpolation: false,
+            dotenv_values: HashMap::new(),
+            errors: vec![],
+            error_report_format: ErrorReportFormat::Text,
+            exactly_one_groups: vec![],
+            at_least_one_groups: vec![],
+            experimental_gate: None,
+            order_constraints: vec![],
+            implications: vec![],
+            relations: vec![],
+            timing_enabled: false,
+            timing: ParseTiming::default(),
+            help_requested: false,
+            version_requested: false,
+            abort_flag: false,
+        }
+    }
+
+    /// Returns a `CommandLineProcessor` with modern conventions instead of the
+    /// backward-compatible settings of `new()`:
+    ///
+    /// * help is requested with `-h`/`--help` instead of `--help`/`--h`;
+    /// * the version is requested with `-V`/`--version` instead of `--version`/`--v`;
+    /// * the help text lists the parameters sorted by name instead of in hash order.
+    ///
+    /// Errors and help go to stderr with both constructors. With either one `try_parse` is
+    /// the non-exiting way to parse, and `parse_command_line` a wrapper that prints and exits.
+    ///
+    /// Help and version aliases are matched before parameters, so registering one of them for a
+    /// parameter panics. To use `-h` for e.g. a host, call `set_help_aliases(&["--help"])` first.
+    pub fn with_defaults() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_help_aliases(&["-h", "--help"]);
+        processor.set_version_aliases(&["-V", "--version"]);
+        processor.set_sorted_help(true);
+
+        processor
+    }
+
+    /// Add a parameter to be parsed.
+    ///
+    /// The `/name` and `--name` aliases are added automatically unless they are help or
+    /// version aliases.
+    ///
+    /// # Panics
+    /// Panics if one of `aliases` is a help or version alias, since it could never match, or
+    /// if another `ParameterType::TrailingText` parameter is already registered.
+    pub fn add_parameter_detail(
+        &mut self,
+        parameter_name: &str,
+        parameter_type: ParameterType,
+        allow_empty: bool,
+        default_value: ParameterValue,
+        description: &str,
+        mut aliases: Vec<String>,
+    ) -> Rc<Parameter> {
+        for alias in &aliases {
+            if let Some(reserved_for) = self.reserved_alias_use(alias) {
+                panic!(
+                    "alias {} of parameter {} is already used for {}",
+                    alias, parameter_name, reserved_for
+                );
+            }
+        }
+        if parameter_type == ParameterType::TrailingText {
+            if let Some(other) = self.trailing_parameter() {
+                if other.parameter_name != parameter_name {
+                    panic!(
+                        "parameter {} is already the trailing text parameter",
+                        &other.parameter_name
+                    );
+                }
+            }
+        }
+
+        let alias1 = "/".to_string() + parameter_name;
+        let alias2 = "--".to_string() + parameter_name;
+        if !aliases.iter().any(|item| item == &alias1) && self.reserved_alias_use(&alias1).is_none()
+        {
+            aliases.push(alias1)
+        }
+        if !aliases.iter().any(|item| item == &alias2) && self.reserved_alias_use(&alias2).is_none()
+        {
+            aliases.push(alias2)
+        }
+
+        let parameter = Rc::new(Parameter {
+            parameter_name: parameter_name.to_owned(),
+            parameter_type,
+            aliases: RefCell::new(aliases),
+            allow_empty,
+            description: description.to_string(),
+            default_value: default_value.clone(),
+            value: RefCell::new(default_value),
+            terminator: RefCell::new(None),
+            radix: Cell::new(None),
+            merge_strategy: Cell::new(MergeStrategy::Override),
+            dedup: Cell::new(false),
+            sorted: Cell::new(false),
+            was_set: Cell::new(false),
+            implied: Cell::new(false),
+            occurrences: Cell::new(0),
+            experimental: Cell::new(false),
+            sensitive: Cell::new(false),
+            env_presence: RefCell::new(None),
+            position: Cell::new(None),
+            description_provider: RefCell::new(None),
+        });
+
+        self.parameters
+            .insert(parameter_name.to_owned(), parameter.clone());
+
+        parameter
+    }
+
+    /// 添加参数
+    pub fn add_simple_parameter(
+        &mut self,
+        parameter_name: &str,
+        parameter_type: ParameterType,
+        description: &str,
+    ) -> Rc<Parameter> {
+        self.add_parameter_detail(
+            parameter_name,
+            parameter_type,
+            false,
+            ParameterValue::None,
+            description,
+            vec![],
+        )
+    }
+
+    /// 添加参数
+    pub fn add_can_empty_parameter(
+        &mut self,
+        parameter_name: &str,
+        parameter_type: ParameterType,
+        default_value: ParameterValue,
+        description: &str,
+    ) -> Rc<Parameter> {
+        self.add_parameter_detail(
+            parameter_name,
+            parameter_type,
+            true,
+            default_value,
+            description,
+            vec![],
+        )
+    }
+
+    /// Add a required parameter whose handle returns `T` directly after parsing.
+    pub fn add_required_typed<T: TypedValue>(
+        &mut self,
+        parameter_name: &str,
+        description: &str,
+        aliases: Vec<String>,
+    ) -> RequiredParameter<T> {
+        let parameter = self.add_parameter_detail(
+            parameter_name,
+            T::parameter_type(),
+            false,
+            ParameterValue::None,
+            description,
+            aliases,
+        );
+
+        RequiredParameter {
+            parameter,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Requires exactly one of the named parameters to be set.
+    ///
+    /// Parsing fails with `one of --a, --b is required` when none is set and with
+    /// `--a and --b are mutually exclusive` when several are.
+    pub fn add_exactly_one_group(&mut self, names: &[&str]) {
+        self.exactly_one_groups
+            .push(names.iter().map(|name| name.to_string()).collect());
+    }
+
+    /// Requires at least one of the named parameters to be set; setting several is fine.
+    ///
+    /// Parsing fails with `at least one of --a, --b is required` when none is set.
+    pub fn add_at_least_one_group(&mut self, names: &[&str]) {
+        self.at_least_one_groups
+            .push(names.iter().map(|name| name.to_string()).collect());
+    }
+
+    /// Requires `before` to appear earlier on the command line than `after` when both are given.
+    /// The first occurrence of each parameter counts.
+    pub fn add_order_constraint(&mut self, before: &str, after: &str) {
+        self.order_constraints
+            .push((before.to_string(), after.to_string()));
+    }
+
+    /// Sets the `implies` flags whenever the `when` flag is set, e.g. `--quiet` implying
+    /// `--no-progress`. Implied flags can imply further flags, and count as set for groups,
+    /// the experimental gate and `export_env`.
+    ///
+    /// An explicit value wins over an implication: a flag given on the command line or in
+    /// the environment, including one set to `false` there, is left as it is.
+    pub fn add_implication(&mut self, when: &str, implies: &[&str]) {
+        self.implications.push((
+            when.to_string(),
+            implies.iter().map(|name| name.to_string()).collect(),
+        ));
+    }
+
+    /// Requires a relationship between the values of two numeric parameters, checked after
+    /// parsing when both have a value. A violation is reported as `--min (10) must be <= --max (5)`.
+    ///
+    /// # Panics
+    /// Panics if either side names a parameter that is not registered yet.
+    pub fn add_relation(&mut self, relation: Relation) {
+        for name in [&relation.left, &relation.right].iter() {
+            assert!(
+                self.parameters.contains_key(name.as_str()),
+                "relation uses unknown parameter {}",
+                name
+            );
+        }
+        self.relations.push(relation);
+    }
+
+    /// Sets the parameter, usually a flag such as `enable-experimental`, that must be set for
+    /// experimental parameters to be accepted. Without a gate experimental parameters are
+    /// always rejected. Checked after parsing.
+    pub fn set_experimental_gate(&mut self, parameter_name: &str) {
+        self.experimental_gate = Some(parameter_name.to_string());
+    }
+
+    /// Parses the program's command line parameters.
+    ///
+    /// The argument following an option that takes a value is always that option's value,
+    /// even when it looks like an option: `--grep --help` searches for `--help` rather than
+    /// printing the help text.
+    ///
+    /// A value written as `@<path>` is replaced by the trimmed contents of that file;
+    /// use `@@` for a value that starts with a literal `@`. Values collected up to a list
+    /// terminator are always taken literally.
+    ///
+    /// Use `try_parse` to handle errors, help and version requests without exiting.
+    ///
+    /// # Exits
+    /// Prints the version text to stdout and exits with 0 when a version alias is given.
+    /// Sends the help text to stderr, or to the help handler, and exits with -1 when a help
+    /// alias is given. Invalid arguments print the errors and the help text to stderr and
+    /// exit with -1, failed validation with -2.
+    pub fn parse_command_line(&mut self) {
+        let args: Vec<String> = env::args().skip(1).collect(); // Skip executable name
+        match self.parse_all(&args) {
+            Ok(ParseOutcome::Parsed) => {}
+            Ok(_) => self.exit_with_errors(-1),
+            Err(code) => self.exit_with_errors(code),
+        }
+    }
+
+    /// Parses `args` like `parse_command_line`, including environment values and validation,
+    /// but returns instead of printing and exiting.
+    ///
+    /// Help and version requests are returned as a `ParseOutcome`; print `help_text` or the
+    /// version text as needed. On failure all recorded errors are returned, as by `errors`.
+    ///
+    /// Values from an earlier `try_parse` or `parse_partial` are discarded first, so the same
+    /// processor can parse several argument lists.
+    pub fn try_parse(&mut self, args: &[String]) -> Result<ParseOutcome, Vec<ParseError>> {
+        self.parse_all(args).map_err(|_| self.errors.clone())
+    }
+
+    /// Resets every parameter and the errors and requests of a previous parse, so a processor
+    /// can parse several argument lists in turn.
+    fn reset_parse_state(&mut self) {
+        for parameter in self.parameters.values() {
+            parameter.reset();
+        }
+        self.errors.clear();
+        self.help_requested = false;
+        self.version_requested = false;
+        self.abort_flag = false;
+    }
+
+    /// Runs every parse stage on `args`. Fails with the exit code for `parse_command_line`:
+    /// -1 for invalid arguments and -2 for failed validation.
+    fn parse_all(&mut self, args: &[String]) -> Result<ParseOutcome, i32> {
+        let mut lap = Instant::now();
+        self.reset_parse_state();
+        for err in self.apply_env_values() {
+            self.report_error(err);
+        }
+        let environment = next_lap(&mut lap);
+
+        self.parse_args(args, false);
+        let arguments = next_lap(&mut lap);
+
+        if self.version_requested {
+            return Ok(ParseOutcome::VersionRequested(self.version_output()));
+        }
+        if self.help_requested {
+            return Ok(ParseOutcome::HelpRequested);
+        }
+        if self.abort_flag {
+            return Err(-1);
+        }
+
+        self.apply_implications();
+        self.interpolate_env_values();
+        self.normalize_path_separators();
+        self.apply_path_base();
+        let post_processing = next_lap(&mut lap);
+
+        let mut errors = self.check_if_parse_all_arg();
+        errors.extend(self.check_groups());
+        errors.extend(self.check_experimental());
+        errors.extend(self.check_order());
+        errors.extend(self.check_relations());
+        if self.timing_enabled {
+            self.timing = ParseTiming {
+                environment,
+                arguments,
+                post_processing,
+                validation: next_lap(&mut lap),
+            };
+        }
+
+        if !errors.is_empty() {
+            for error in errors {
+                self.report_error(error);
+            }
+            return Err(-2);
+        }
+
+        Ok(ParseOutcome::Parsed)
+    }
+
+    /// Parses the known parameters in `args` and returns every argument it did not recognize, in order.
+    ///
+    /// Unknown arguments and missing required parameters are not errors, which makes this usable
+    /// as a first pass that handles global options and hands the rest to another parser.
+    /// `--help` and `--version` are also returned rather than handled. Values that fail to
+    /// convert still set `abort_flag` and are listed by `errors`. Environment values and post-parse path handling are
+    /// only applied by `parse_command_line` and `try_parse`.
+    ///
+    /// Free words are returned as well, so a `ParameterType::TrailingText` parameter is only
+    /// set by words that follow its own alias. Like `try_parse` it starts from the default
+    /// values, discarding those of an earlier parse.
+    pub fn parse_partial(&mut self, args: &[String]) -> Vec<String> {
+        let mut lap = Instant::now();
+        self.reset_parse_state();
+        let leftovers = self.parse_args(args, true);
+        if self.timing_enabled {
+            self.timing = ParseTiming {
+                arguments: next_lap(&mut lap),
+                ..ParseTiming::default()
+            };
+        }
+        leftovers
+    }
+
+    /// Splits `args` into the recognized options and everything else, both in their original order.
+    ///
+    /// This is purely syntactic: values are not converted, nothing is assigned and no validation
+    /// runs. Each option is returned as its parameter name with the following argument as its
+    /// value, or an empty value for flags. A terminated list yields one pair per collected value.
+    /// A value attached with `=`, as in `-p=x`, is split off the same way as when parsing.
+    pub fn split_options(&self, args: &[String]) -> (Vec<(String, String)>, Vec<String>) {
+        let mut options = vec![];
+        let mut others = vec![];
+        let mut iter = args.iter();
+
+        while let Some(argument) = iter.next() {
+            let (option, inline_value) = self.split_inline_value(argument);
+            let parameter = match self.resolve_alias(option) {
+                Some(parameter) => parameter,
+                None => {
+                    others.push(argument.clone());
+                    continue;
+                }
+            };
+
+            let name = &parameter.parameter_name;
+            match (&parameter.parameter_type, &*parameter.terminator.borrow()) {
+                (ParameterType::Flag, _) => {
+                    options.push((name.clone(), inline_value.unwrap_or_default()))
+                }
+                (ParameterType::List, Some(terminator)) => {
+                    if let Some(val) = inline_value {
+                        options.push((name.clone(), val));
+                    }
+                    for val in iter.by_ref() {
+                        if val == terminator {
+                            break;
+                        }
+                        options.push((name.clone(), val.clone()));
+                    }
+                }
+                _ => {
+                    let val = inline_value
+                        .or_else(|| iter.next().cloned())
+                        .unwrap_or_default();
+                    options.push((name.clone(), val));
+                }
+            }
+        }
+
+        (options, others)
+    }
+
+    /// Returns the `ParameterType::TrailingText` parameter, if one is registered.
+    fn trailing_parameter(&self) -> Option<Rc<Parameter>> {
+        self.parameters
+            .values()
+            .find(|parameter| parameter.parameter_type == ParameterType::TrailingText)
+            .cloned()
+    }
+
+    /// Splits `argument` into the option and the value attached with `=`, if any.
+    ///
+    /// `--name=value`, `-n=value` and `/name=value` carry their value after the first `=`, so
+    /// `-p=x` yields `x` and `-p=` an empty value. An alias that itself contains `=` still
+    /// matches as a whole.
+    fn split_inline_value<'a>(&self, argument: &'a str) -> (&'a str, Option<String>) {
+        match argument.split_once('=') {
+            Some((option, value))
+                if self.resolve_alias(argument).is_none()
+                    && self.resolve_alias(option).is_some() =>
+            {
+                (option, Some(value.to_string()))
+            }
+            _ => (argument, None),
+        }
+    }
+
+    /// Assigns the values in `args` to the matching parameters and returns the unrecognized arguments.
+    ///
+    /// Unrecognized arguments are only collected when `partial` is set. Otherwise arguments that
+    /// do not look like an option go to the `ParameterType::TrailingText` parameter if there is
+    /// one, and the rest abort parsing, see `looks_like_option`.
+    fn parse_args(&mut self, args: &[String], partial: bool) -> Vec<String> {
+        let mut leftovers = vec![];
+        // list values from lower layers and from the command line, per parameter name
+        let mut list_layers: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+        // words collected for the `ParameterType::TrailingText` parameter
+        let mut trailing_words: Vec<String> = vec![];
+        let trailing_parameter = self.trailing_parameter();
+        let mut iter = args.iter().cloned();
+
+        // only arguments in option position are matched here; the value branches below take
+        // their value straight from `iter`, so a value such as `--help` is never interpreted
+        while let Some(argument) = iter.next() {
+            let position = args.len() - iter.len() - 1;
+            match argument.as_str() {
+                arg if !partial && self.help_aliases.iter().any(|x| x == arg) => {
+                    // the help text is printed once by the abort handling of the caller
+                    self.help_requested = true;
+                    self.abort_flag = true;
+                    break;
+                }
+                arg if !partial && self.version_aliases.iter().any(|x| x == arg) => {
+                    // the version text is printed by the abort handling of the caller
+                    self.version_requested = true;
+                    self.abort_flag = true;
+                    break;
+                }
+                arg => {
+                    let mut parameter_exists = false;
+                    let (arg, mut inline_value) = self.split_inline_value(arg);
+
+                    let parameters: Vec<Rc<Parameter>> =
+                        self.parameters.values().cloned().collect();
+                    for parameter in &parameters {
+                        let name = &parameter.parameter_name;
+                        if parameter.has_alias(arg) {
+                            parameter_exists = true;
+                            parameter.was_set.set(true);
+                            parameter.occurrences.set(parameter.occurrences.get() + 1);
+                            if parameter.position.get().is_none() {
+                                parameter.position.set(Some(position));
+                            }
+
+                            match parameter.parameter_type {
+                                ParameterType::Flag => {
+                                    if let Some(val) = inline_value.take() {
+                                        self.report_invalid_value(
+                                            name,
+                                            Some(&val),
+                                            format!("Parameter {} does not take a value", name),
+                                        );
+                                        break;
+                                    }
+                                    *parameter.value.borrow_mut() = ParameterValue::Flag
+                                }
+                                ParameterType::List => {
+                                    let (lower, values) =
+                                        list_layers.entry(name.clone()).or_insert_with(|| {
+                                            let lower = match &*parameter.value.borrow() {
+                                                ParameterValue::List(values) => values.clone(),
+                                                _ => vec![],
+                                            };
+                                            (lower, vec![])
+                                        });
+
+                                    match &*parameter.terminator.borrow() {
+                                        Some(terminator) => {
+                                            let mut terminated = false;
+                                            if let Some(val) = inline_value.take() {
+                                                values.push(val);
+                                            }
+                                            for val in iter.by_ref() {
+                                                if &val == terminator {
+                                                    terminated = true;
+                                                    break;
+                                                }
+                                                values.push(val);
+                                            }
+
+                                            if !terminated {
+                                                self.report_error(ParseError::with_kind(
+                                                    ParseErrorKind::MissingValue,
+                                                    Some(name),
+                                                    Some(&argument),
+                                                    format!(
+                                                        "Missing terminator {} for parameter {}",
+                                                        terminator, name
+                                                    ),
+                                                ));
+                                                break;
+                                            }
+                                        }
+                                        None => match inline_value
+                                            .take()
+                                            .or_else(|| iter.next())
+                                            .map(read_value_file)
+                                        {
+                                            Some(Ok(val)) => match parameter.parse_value(&val) {
+                                                Ok(ParameterValue::List(items)) => {
+                                                    values.extend(items)
+                                                }
+                                                Ok(_) => {}
+                                                Err(err) => {
+                                                    self.report_invalid_value(
+                                                        name,
+                                                        Some(&val),
+                                                        err,
+                                                    );
+                                                    break;
+                                                }
+                                            },
+                                            Some(Err(err)) => {
+                                                self.report_invalid_value(name, None, err);
+                                                break;
+                                            }
+                                            None => {
+                                                self.report_missing_value(name, &argument);
+                                                break;
+                                            }
+                                        },
+                                    }
+
+                                    *parameter.value.borrow_mut() =
+                                        ParameterValue::List(parameter.finish_list(
+                                            parameter.merge_strategy.get().merge(lower, values),
+                                        ));
+                                }
+                                ParameterType::TrailingText => {
+                                    match inline_value
+                                        .take()
+                                        .or_else(|| iter.next())
+                                        .map(read_value_file)
+                                    {
+                                        Some(Ok(val)) => trailing_words.push(val),
+                                        Some(Err(err)) => {
+                                            self.report_invalid_value(name, None, err);
+                                            break;
+                                        }
+                                        None => {
+                                            self.report_missing_value(name, &argument);
+                                            break;
+                                        }
+                                    }
+                                }
+                                _ => {
+                                    match inline_value
+                                        .take()
+                                        .or_else(|| iter.next())
+                                        .map(read_value_file)
+                                    {
+                                        Some(Ok(val)) => {
+                                            if val.is_empty() {
+                                                if !parameter.allow_empty {
+                                                    self.report_missing_value(name, &argument);
+                                                    break;
+                                                }
+                                                continue;
+                                            }
+
+                                            match parameter.parse_value(&val) {
+                                                Ok(val) => *parameter.value.borrow_mut() = val,
+                                                Err(err) => {
+                                                    self.report_invalid_value(
+                                                        name,
+                                                        Some(&val),
+                                                        format!(
+                                                            "Unable to convert parameter {}\n{}",
+                                                            name, err
+                                                        ),
+                                                    );
+                                                    break;
+                                                }
+                                            }
+                                        }
+                                        Some(Err(err)) => {
+                                            self.report_invalid_value(name, None, err);
+                                            break;
+                                        }
+                                        None => {
+                                            if !parameter.allow_empty {
+                                                self.report_missing_value(name, &argument);
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if !parameter_exists {
+                        if partial {
+                            leftovers.push(argument.clone());
+                            continue;
+                        }
+
+                        if trailing_parameter.is_some() && !looks_like_option(arg) {
+                            trailing_words.push(argument.clone());
+                            continue;
+                        }
+
+                        self.report_error(ParseError::with_kind(
+                            ParseErrorKind::UnknownParameter,
+                            None,
+                            Some(arg),
+                            format!("Unknown parameter: {}", arg),
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(parameter) = trailing_parameter {
+            if !trailing_words.is_empty() {
+                *parameter.value.borrow_mut() = ParameterValue::String(trailing_words.join(" "));
+                parameter.was_set.set(true);
+            }
+        }
+
+        leftovers
+    }
+
+    /// Expands environment variables in the `String` and `Path` values that were set.
+    fn interpolate_env_values(&self) {
+        if !self.env_interpolation {
+            return;
+        }
+
+        for parameter in self.parameters.values() {
+            if !parameter.was_set() {
+                continue;
+            }
+
+            let mut value = parameter.value.borrow_mut();
+            let interpolated = match &*value {
+                ParameterValue::String(val) => ParameterValue::String(interpolate_env(val)),
+                ParameterValue::Path(val) => {
+                    ParameterValue::Path(PathBuf::from(interpolate_env(&val.to_string_lossy())))
+                }
+                _ => continue,
+            };
+            *value = interpolated;
+        }
+    }
+
+    /// Rewrites the separators of relative path values to the configured separator.
+    ///
+    /// Absolute paths (`/x`, `\x`, `C:\x`) and UNC paths (`\\server\share`) are left untouched.
+    fn normalize_path_separators(&self) {
+        let separator = match self.path_separator {
+            Some(separator) => separator,
+            None => return,
+        };
+
+        for parameter in self.parameters.values() {
+            let mut value = parameter.value.borrow_mut();
+            if let ParameterValue::Path(path) = &*value {
+                let raw = path.to_string_lossy();
+                let is_absolute =
+                    raw.starts_with('/') || raw.starts_with('\\') || has_drive_prefix(&raw);
+                if is_absolute {
+                    continue;
+                }
+
+                let normalized: String = raw
+                    .chars()
+                    .map(|c| if c == '/' || c == '\\' { separator } else { c })
+                    .collect();
+                *value = ParameterValue::Path(PathBuf::from(normalized));
+            }
+        }
+    }
+
+    /// Joins relative path values onto the configured base directory.
+    fn apply_path_base(&self) {
+        let base = match &self.path_base {
+            Some(base) => base,
+            None => return,
+        };
+
+        for parameter in self.parameters.values() {
+            let mut value = parameter.value.borrow_mut();
+            if let ParameterValue::Path(path) = &*value {
+                if path.is_relative() {
+                    *value = ParameterValue::Path(base.join(path));
+                }
+            }
+        }
+    }
+
+    /// Returns an error for every required parameter without a value.
+    fn check_if_parse_all_arg(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        for item in self.parameters.values() {
+            if item.allow_empty {
+                continue;
+            }
+
+            if item.value.borrow().is_none() {
+                errors.push(ParseError::with_kind(
+                    ParseErrorKind::MissingRequired,
+                    Some(&item.parameter_name),
+                    None,
+                    format!("cmd arg {} is no set", &item.parameter_name),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Returns true if the named parameter exists and was set to a value, see
+    /// `Parameter::has_set_value`. A flag turned off from the environment was set, but to no
+    /// value, so it does not count; a flag turned on by an implication does.
+    fn is_parameter_set(&self, parameter_name: &str) -> bool {
+        matches!(self.parameters.get(parameter_name), Some(parameter) if parameter.has_set_value())
+    }
+
+    /// Sets the flags implied by set flags that were not given explicitly, until nothing changes.
+    fn apply_implications(&self) {
+        let is_flag_on = |name: &str| {
+            self.parameters
+                .get(name)
+                .is_some_and(|parameter| *parameter.value.borrow() == ParameterValue::Flag)
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (when, implies) in &self.implications {
+                if !is_flag_on(when) {
+                    continue;
+                }
+
+                for name in implies {
+                    match self.parameters.get(name) {
+                        Some(parameter)
+                            if parameter.parameter_type == ParameterType::Flag
+                                && !parameter.was_set()
+                                && !is_flag_on(name) =>
+                        {
+                            *parameter.value.borrow_mut() = ParameterValue::Flag;
+                            parameter.implied.set(true);
+                            changed = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns an error for every violated exactly-one-of and at-least-one-of group.
+    fn check_groups(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        for group in &self.exactly_one_groups {
+            let set_names: Vec<&String> = group
+                .iter()
+                .filter(|name| self.is_parameter_set(name))
+                .collect();
+
+            let message = match set_names.len() {
+                1 => continue,
+                0 => format!("one of {} is required", join_option_names(group, ", ")),
+                _ => format!(
+                    "{} are mutually exclusive",
+                    join_option_names(&set_names, " and ")
+                ),
+            };
+            errors.push(ParseError::with_kind(
+                ParseErrorKind::Constraint,
+                None,
+                None,
+                message,
+            ));
+        }
+
+        for group in &self.at_least_one_groups {
+            if !group.iter().any(|name| self.is_parameter_set(name)) {
+                errors.push(ParseError::with_kind(
+                    ParseErrorKind::Constraint,
+                    None,
+                    None,
+                    format!(
+                        "at least one of {} is required",
+                        join_option_names(group, ", ")
+                    ),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Returns an error for every experimental parameter used without the experimental gate.
+    fn check_experimental(&self) -> Vec<ParseError> {
+        let gate_open = match &self.experimental_gate {
+            Some(gate) => self.is_parameter_set(gate),
+            None => false,
+        };
+        if gate_open {
+            return vec![];
+        }
+
+        let mut errors = vec![];
+        for parameter in self.parameters.values() {
+            if !parameter.experimental.get() || !parameter.has_set_value() {
+                continue;
+            }
+
+            let message = match &self.experimental_gate {
+                Some(gate) => format!(
+                    "--{} is experimental; pass --{} to use it",
+                    &parameter.parameter_name, gate
+                ),
+                None => format!("--{} is experimental", &parameter.parameter_name),
+            };
+            errors.push(ParseError::with_kind(
+                ParseErrorKind::Constraint,
+                Some(&parameter.parameter_name),
+                None,
+                message,
+            ));
+        }
+
+        errors
+    }
+
+    /// Returns an error for every order constraint violated by parameters that both appeared.
+    fn check_order(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        for (before, after) in &self.order_constraints {
+            let before_position = self.parameters.get(before).and_then(|p| p.position());
+            let after_position = self.parameters.get(after).and_then(|p| p.position());
+
+            if let (Some(before_position), Some(after_position)) = (before_position, after_position)
+            {
+                if before_position > after_position {
+                    errors.push(ParseError::with_kind(
+                        ParseErrorKind::Constraint,
+                        Some(after),
+                        None,
+                        format!("--{} must appear before --{}", before, after),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Returns an error for every relation that does not hold between two numeric values.
+    /// Relations with a missing or non-numeric side are skipped.
+    fn check_relations(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        for relation in &self.relations {
+            let (left, right) = match (
+                self.parameters.get(&relation.left),
+                self.parameters.get(&relation.right),
+            ) {
+                (Some(left), Some(right)) => (left.get_value(), right.get_value()),
+                _ => continue,
+            };
+
+            let ordering = match left.compare_numeric(&right) {
+                Some(ordering) => ordering,
+                None => continue,
+            };
+            if !relation.comparison.holds(ordering) {
+                errors.push(ParseError::with_kind(
+                    ParseErrorKind::Constraint,
+                    Some(&relation.left),
+                    None,
+                    format!(
+                        "--{} ({}) must be {} --{} ({})",
+                        relation.left,
+                        left.to_help_string(),
+                        relation.comparison.symbol(),
+                        relation.right,
+                        right.to_help_string()
+                    ),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Records a parse error and marks parsing as aborted.
+    fn report_error(&mut self, error: ParseError) {
+        self.errors.push(error);
+        self.abort_flag = true;
+    }
+
+    /// Records that the option `argument` of parameter `name` has no value.
+    fn report_missing_value(&mut self, name: &str, argument: &str) {
+        self.report_error(ParseError::with_kind(
+            ParseErrorKind::MissingValue,
+            Some(name),
+            Some(argument),
+            format!("No value passed for parameter {}", name),
+        ));
+    }
+
+    /// Records that a value of parameter `name` could not be used.
+    fn report_invalid_value(&mut self, name: &str, value: Option<&str>, message: String) {
+        self.report_error(ParseError::with_kind(
+            ParseErrorKind::InvalidValue,
+            Some(name),
+            value,
+            message,
+        ));
+    }
+
+    /// Returns the errors recorded by the last parse.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Sets how errors are reported when `parse_command_line` aborts. Defaults to
+    /// `ErrorReportFormat::Text`.
+    pub fn set_error_report_format(&mut self, error_report_format: ErrorReportFormat) {
+        self.error_report_format = error_report_format;
+    }
+
+    /// Returns the recorded errors as a JSON array of objects with `kind`, `parameter`,
+    /// `argument` and `message` fields.
+    pub fn errors_to_json(&self) -> String {
+        let objects: Vec<String> = self.errors.iter().map(|error| error.to_json()).collect();
+        format!("[{}]", objects.join(","))
+    }
+
+    /// Returns what aborting with `code` prints and the exit code actually used. A version
+    /// request goes to stdout and exits with 0; errors and the help text go to stderr.
+    fn exit_report(&self, code: i32) -> ExitReport {
+        if self.version_requested {
+            return ExitReport {
+                stdout: format!("{}\n", self.version_output()),
+                stderr: String::new(),
+                help: false,
+                code: 0,
+            };
+        }
+
+        let (stderr, help) = match self.error_report_format {
+            ErrorReportFormat::Json if !self.errors.is_empty() => {
+                (format!("{}\n", self.errors_to_json()), false)
+            }
+            _ if self.errors.is_empty() => (String::new(), true),
+            _ => (format!("{}\n\n", format_error_block(&self.errors)), true),
+        };
+
+        ExitReport {
+            stdout: String::new(),
+            stderr,
+            help,
+            code,
+        }
+    }
+
+    /// Prints the version text, or reports the recorded errors in the configured format,
+    /// and exits, see `exit_report`.
+    fn exit_with_errors(&self, code: i32) -> ! {
+        let report = self.exit_report(code);
+        print!("{}", report.stdout);
+        eprint!("{}", report.stderr);
+        if report.help {
+            self.print_help_text();
+        }
+
+        std::process::exit(report.code);
+    }
+
+    /// Print the default help text to stderr, like the parse errors, so stdout stays clean.
+    fn print_help_text(&self) {
+        let help_text = self.help_text();
+        match &self.help_handler {
+            Some(help_handler) => help_handler(&help_text),
+            None => eprint!("{}", help_text),
+        }
+    }
+
+    /// Sets the function that receives the help text instead of it being printed,
+    /// e.g. to show it through a pager or to colorize it.
+    pub fn set_help_handler(&mut self, help_handler: HelpHandler) {
+        self.help_handler = Some(help_handler);
+    }
+
+    /// Returns the help text: usage line followed by the option list.
+    pub fn help_text(&self) -> String {
+        let mut text = format!(
+            "USAGE \r\n\t{} [OPTIONS]\r\n\n",
+            std::env::current_exe().unwrap().to_str().unwrap()
+        );
+        text.push_str("OPTIONS\n");
+
+        if self.table_help {
+            self.write_help_table(&mut text);
+        } else {
+            self.write_help_columns(&mut text);
+        }
+
+        text
+    }
+
+    /// Returns the parameters in the order they are listed in the help text.
+    fn help_parameters(&self) -> Vec<&Rc<Parameter>> {
+        let mut parameters: Vec<&Rc<Parameter>> = self.parameters.values().collect();
+        if self.sorted_help {
+            parameters.sort_by(|a, b| a.parameter_name.cmp(&b.parameter_name));
+        }
+
+        parameters
+    }
+
+    /// Writes the options as a table with one column per attribute.
+    fn write_help_table(&self, text: &mut String) {
+        let mut param_str_list: Vec<Vec<String>> = vec![];
+        param_str_list.push(vec![
+            "arg".to_string(),
+            "IsCanEmpty".to_string(),
+            "DefaultValue".to_string(),
+            "Description".to_string(),
+        ]);
+        for item in self.help_parameters() {
+            // name[alias1,alias2] can empty default value description
+
+            let arg_name = item.aliases.borrow().join(",");
+            let mut can_empty = "false";
+            if item.allow_empty {
+                can_empty = "true";
+            }
+
+            let default_value = item.default_help_string();
+            param_str_list.push(vec![
+                arg_name,
+                can_empty.to_string(),
+                default_value.to_string(),
+                item.help_description(),
+            ]);
+        }
+
+        // calculate width
+        let mut col_max_width: [usize; 4] = [0, 0, 0, 0];
+        for arg_item in &param_str_list {
+            for col_index in 0..arg_item.len() {
+                let tmp_len = arg_item[col_index].len();
+                if tmp_len > col_max_width[col_index] {
+                    col_max_width[col_index] = tmp_len;
+                }
+            }
+        }
+
+        // print
+        for arg_item in &param_str_list {
+            text.push_str(&format!("\t{name:name_width$}\t{can_empty:can_empty_width$}\t{default_value:default_value_width$}\t{description:description_width$}\n",
+                     name=arg_item[0],name_width=col_max_width[0]
+                     ,can_empty=arg_item[1],can_empty_width=col_max_width[1]
+                     ,default_value=arg_item[2],default_value_width=col_max_width[2]
+                     ,description=arg_item[3],description_width=col_max_width[3]));
+        }
+    }
+
+    /// Writes the options as two aligned columns, wrapping descriptions to the help width.
+    fn write_help_columns(&self, text: &mut String) {
+        const INDENT: usize = 2;
+        const GUTTER: usize = 4;
+        const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+        let mut rows: Vec<(String, String)> = vec![];
+        for item in self.help_parameters() {
+            let mut arg_name = item.aliases.borrow().join(", ");
+            if let Some(placeholder) = item.parameter_type.value_placeholder() {
+                arg_name = format!("{} <{}>", arg_name, placeholder);
+            }
+
+            let mut description = item.help_description();
+            let default_value = item.default_help_string();
+            if !default_value.is_empty() {
+                description = format!("{} [default: {}]", description, default_value);
+            }
+            if !item.allow_empty {
+                description = format!("{} [required]", description);
+            }
+
+            rows.push((arg_name, description));
+        }
+
+        let name_width = rows
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let description_width = self
+            .help_width
+            .saturating_sub(INDENT + name_width + GUTTER)
+            .max(MIN_DESCRIPTION_WIDTH);
+
+        for (name, description) in &rows {
+            let lines = wrap_text(description, description_width);
+            for (index, line) in lines.iter().enumerate() {
+                let left = if index == 0 { name.as_str() } else { "" };
+                let row = format!(
+                    "{:indent$}{:name_width$}{:gutter$}{}",
+                    "",
+                    left,
+                    "",
+                    line,
+                    indent = INDENT,
+                    name_width = name_width,
+                    gutter = GUTTER
+                );
+                text.push_str(row.trim_end());
+                text.push('\n');
+            }
+        }
+    }
+
+    /// Sets the line width used to wrap descriptions in the help text. Defaults to 80.
+    pub fn set_help_width(&mut self, width: usize) {
+        self.help_width = width;
+    }
+
+    /// Lists the parameters in the help text sorted by name instead of in hash order.
+    pub fn set_sorted_help(&mut self, sorted_help: bool) {
+        self.sorted_help = sorted_help;
+    }
+
+    /// Sets the arguments that print the help text. Defaults to `--help` and `--h`.
+    ///
+    /// # Panics
+    /// Panics if one of `aliases` belongs to a registered parameter.
+    pub fn set_help_aliases(&mut self, aliases: &[&str]) {
+        self.assert_aliases_unused(aliases);
+        self.help_aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+    }
+
+    /// Sets the arguments that print the version text. Defaults to `--version` and `--v`.
+    ///
+    /// # Panics
+    /// Panics if one of `aliases` belongs to a registered parameter.
+    pub fn set_version_aliases(&mut self, aliases: &[&str]) {
+        self.assert_aliases_unused(aliases);
+        self.version_aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+    }
+
+    /// Panics if one of `aliases` belongs to a registered parameter, which it would shadow.
+    fn assert_aliases_unused(&self, aliases: &[&str]) {
+        for alias in aliases {
+            if let Some(owner) = self.resolve_alias(alias) {
+                panic!(
+                    "alias {} is already used by parameter {}",
+                    alias, &owner.parameter_name
+                );
+            }
+        }
+    }
+
+    /// Prints the help options as the original four column table instead of the two column layout.
+    pub fn set_table_help(&mut self, table_help: bool) {
+        self.table_help = table_help;
+    }
+
+    /// Sets the prefix used to look up parameters in the environment.
+    ///
+    /// With prefix `MYAPP`, parameter `log-level` is read from `MYAPP_LOG_LEVEL`.
+    /// Environment values have lower precedence than command line arguments.
+    pub fn set_env_prefix(&mut self, prefix: &str) {
+        self.env_prefix = Some(prefix.to_owned());
+    }
+
+    /// Loads `KEY=VALUE` lines from a `.env` file (defaults to `./.env`).
+    ///
+    /// The values are used as fallbacks for the environment variables named by
+    /// `set_env_prefix`, so real environment variables and command line arguments win.
+    /// Blank lines and lines starting with `#` are skipped, and values may be wrapped in
+    /// single or double quotes.
+    pub fn load_dotenv(&mut self, path: Option<&Path>) -> Result<(), String> {
+        let path = path.unwrap_or_else(|| Path::new(".env"));
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}\n{}", path.display(), err))?;
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => {
+                    return Err(format!(
+                        "{}:{} expected KEY=VALUE",
+                        path.display(),
+                        index + 1
+                    ))
+                }
+            };
+
+            let value = if value.len() >= 2
+                && ((value.starts_with('"') && value.ends_with('"'))
+                    || (value.starts_with('\'') && value.ends_with('\'')))
+            {
+                &value[1..value.len() - 1]
+            } else {
+                value
+            };
+
+            self.dotenv_values
+                .insert(key.to_string(), value.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the environment variable name for a parameter, if an env prefix is set.
+    fn env_name(&self, parameter_name: &str) -> Option<String> {
+        self.env_prefix
+            .as_ref()
+            .map(|prefix| env_var_name(prefix, parameter_name))
+    }
+
+    /// Returns `export PREFIX_NAME='value'` lines for every parameter set on the command line,
+    /// in the environment or by an implication, for use as `eval "$(mytool --export-env)"`.
+    /// Defaults are not exported.
+    ///
+    /// Values are single-quoted for the shell and sensitive parameters are skipped.
+    /// An empty prefix exports plain `NAME` variables.
+    pub fn export_env(&self, prefix: &str) -> String {
+        let mut names: Vec<&String> = self.parameters.keys().collect();
+        names.sort();
+
+        let mut text = String::new();
+        for name in names {
+            let parameter = &self.parameters[name];
+            let value = parameter.value.borrow();
+            if parameter.sensitive.get() || !parameter.has_set_value() {
+                continue;
+            }
+
+            text.push_str(&format!(
+                "export {}={}\n",
+                env_var_name(prefix, name),
+                shell_quote(&value.to_help_string())
+            ));
+        }
+
+        text
+    }
+
+    /// Assigns values from the environment and the loaded `.env` file. Returns one error per
+    /// invalid value, ordered by parameter name, so all of them can be reported at once.
+    fn apply_env_values(&self) -> Vec<ParseError> {
+        let mut errors = vec![];
+        let mut parameters: Vec<&Rc<Parameter>> = self.parameters.values().collect();
+        parameters.sort_by(|a, b| a.parameter_name.cmp(&b.parameter_name));
+        for parameter in parameters {
+            if parameter.parameter_type == ParameterType::Flag {
+                if let Some(env_name) = &*parameter.env_presence.borrow() {
+                    let present = match env::var(env_name) {
+                        Ok(raw) => !raw.is_empty(),
+                        Err(_) => self
+                            .dotenv_values
+                            .get(env_name)
+                            .is_some_and(|raw| !raw.is_empty()),
+                    };
+                    if present {
+                        *parameter.value.borrow_mut() = ParameterValue::Flag;
+                        parameter.was_set.set(true);
+                        continue;
+                    }
+                }
+            }
+
+            let env_name = match self.env_name(&parameter.parameter_name) {
+                Some(env_name) => env_name,
+                None => continue,
+            };
+
+            let raw = match env::var(&env_name) {
+                Ok(raw) => raw,
+                Err(_) => match self.dotenv_values.get(&env_name) {
+                    Some(raw) => raw.clone(),
+                    None => continue,
+                },
+            };
+
+            match parameter.parse_value(&raw) {
+                Ok(value) => {
+                    *parameter.value.borrow_mut() = value;
+                    parameter.was_set.set(true);
+                }
+                Err(err) => errors.push(ParseError::with_kind(
+                    ParseErrorKind::InvalidValue,
+                    Some(&parameter.parameter_name),
+                    Some(&raw),
+                    format!(
+                        "Invalid value in {} for parameter {}\n{}",
+                        env_name, &parameter.parameter_name, err
+                    ),
+                )),
+            }
+        }
+
+        errors
+    }
+
+    /// Sets the directory that relative `ParameterType::Path` values are resolved against.
+    ///
+    /// The join happens after parsing and before the required-parameter checks;
+    /// absolute paths are left untouched.
+    pub fn set_path_base(&mut self, base: PathBuf) {
+        self.path_base = Some(base);
+    }
+
+    /// Enables expansion of `$VAR` and `${VAR}` in `String` and `Path` values read from the
+    /// command line or the environment. `$$` yields a literal `$`, and undefined variables
+    /// expand to an empty string. Defaults are not expanded.
+    pub fn set_env_interpolation(&mut self, env_interpolation: bool) {
+        self.env_interpolation = env_interpolation;
+    }
+
+    /// Sets the separator that `/` and `\` in relative `ParameterType::Path` values are rewritten to.
+    ///
+    /// Pass `Some(std::path::MAIN_SEPARATOR)` for the platform separator, or `None` to keep
+    /// paths as typed. Runs after parsing, before `set_path_base` is applied.
+    pub fn set_path_separator_normalization(&mut self, separator: Option<char>) {
+        self.path_separator = separator;
+    }
+
+    /// Sets the text to print when the `--version` parameter is used.
+    pub fn set_version_text(&mut self, version_text: &str) {
+        self.version_text = Some(version_text.to_owned());
+    }
+
+    /// Computes the version text only when the `--version` parameter is used, e.g. to include
+    /// runtime information. Takes precedence over `set_version_text` if both are set.
+    pub fn set_version_provider(&mut self, version_provider: TextProvider) {
+        self.version_provider = Some(version_provider);
+    }
+
+    /// Returns the version text, or a default message if neither a version provider nor a
+    /// version text is set.
+    fn version_output(&self) -> String {
+        match (&self.version_provider, &self.version_text) {
+            (Some(version_provider), _) => version_provider(),
+            (None, Some(version_text)) => version_text.clone(),
+            (None, None) => "No version text has been set.".to_string(),
+        }
+    }
+
+    /// Returns the `ParameterValue` for the specified parameter. Returns `ParameterValue::None` if the parameter doesn't exist.
+    pub fn get_parameter_value(&self, parameter_name: &str) -> Option<Ref<'_, ParameterValue>> {
+        self.parameters
+            .get(parameter_name)
+            .map(|parameter| parameter.value.borrow())
+    }
+
+    /// Returns true if both processors define the same parameters: names, types, aliases,
+    /// emptiness, defaults, descriptions, list terminators, radixes, merge strategies, the
+    /// dedup and sorted settings, the experimental and sensitive markers and `env_presence`
+    /// variables. Parsed values, description providers and processor-wide settings such as
+    /// groups or help options are not compared.
+    pub fn definition_eq(&self, other: &CommandLineProcessor) -> bool {
+        if self.parameters.len() != other.parameters.len() {
+            return false;
+        }
+
+        self.parameters
+            .iter()
+            .all(|(name, parameter)| match other.parameters.get(name) {
+                Some(other_parameter) => parameter.definition_eq(other_parameter),
+                None => false,
+            })
+    }
+
+    /// Returns the `ParameterValue` of the parameter that owns the specified alias, e.g. `-p` or `/path`.
+    pub fn get_value_by_alias(&self, alias: &str) -> Option<Ref<'_, ParameterValue>> {
+        self.resolve_alias(alias)
+            .map(|parameter| parameter.value.borrow())
+    }
+
+    /// Returns `help` or `version` if `alias` prints the help or version text.
+    fn reserved_alias_use(&self, alias: &str) -> Option<&'static str> {
+        if self.help_aliases.iter().any(|x| x == alias) {
+            Some("help")
+        } else if self.version_aliases.iter().any(|x| x == alias) {
+            Some("version")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the parameter that owns the specified alias.
+    fn resolve_alias(&self, alias: &str) -> Option<&Rc<Parameter>> {
+        self.parameters
+            .values()
+            .find(|parameter| parameter.has_alias(alias))
+    }
+
+    /// Adds an alias to an already registered parameter, e.g. to keep a renamed option's
+    /// old spelling working. Fails if the parameter does not exist or the alias is taken,
+    /// including by the help and version aliases, which are matched first.
+    pub fn add_alias(&mut self, parameter_name: &str, alias: &str) -> Result<(), String> {
+        if let Some(reserved_for) = self.reserved_alias_use(alias) {
+            return Err(format!(
+                "alias {} is already used for {}",
+                alias, reserved_for
+            ));
+        }
+        if let Some(owner) = self.resolve_alias(alias) {
+            return Err(format!(
+                "alias {} is already used by parameter {}",
+                alias, &owner.parameter_name
+            ));
+        }
+
+        match self.parameters.get(parameter_name) {
+            Some(parameter) => {
+                parameter.aliases.borrow_mut().push(alias.to_string());
+                Ok(())
+            }
+            None => Err(format!("Unknown parameter: {}", parameter_name)),
+        }
+    }
+
+    /// Returns the parameter registered under the specified name.
+    pub fn get_parameter(&self, parameter_name: &str) -> Option<Rc<Parameter>> {
+        self.parameters.get(parameter_name).cloned()
+    }
+
+    /// Returns how many times the named parameter appeared on the command line, under any alias.
+    /// Returns 0 for unknown parameters.
+    pub fn occurrences(&self, parameter_name: &str) -> usize {
+        self.parameters
+            .get(parameter_name)
+            .map_or(0, |parameter| parameter.occurrences())
+    }
+
+    /// Builds `T` from the parsed parameter values.
+    pub fn extract<T: FromCommandLine>(&self) -> Result<T, ParseError> {
+        T::from_processor(self)
+    }
+
+    /// Resets a single parameter back to its default value (`ParameterValue::None` if it has none).
+    ///
+    /// A cleared required parameter without a default is reported as not set by later validation.
+    pub fn clear_value(&self, parameter_name: &str) -> Result<(), String> {
+        match self.parameters.get(parameter_name) {
+            Some(parameter) => {
+                parameter.reset();
+                Ok(())
+            }
+            None => Err(format!("Unknown parameter: {}", parameter_name)),
+        }
+    }
+
+    /// Returns true if the `CommandLineProcessor` reads `--help` or `--version` in the parameter list.
+    pub fn abort_flag(&self) -> bool {
+        self.abort_flag
+    }
+
+    /// Records how long each stage of `parse_command_line`, `try_parse` and `parse_partial`
+    /// takes, for startup profiling. Off by default.
+    pub fn set_timing_enabled(&mut self, timing_enabled: bool) {
+        self.timing_enabled = timing_enabled;
+    }
+
+    /// Returns the stage durations of the last parse. All durations are zero unless timing
+    /// was enabled; `parse_partial` only records `arguments`.
+    pub fn parse_timing(&self) -> ParseTiming {
+        self.timing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn required_typed_get_returns_parsed_values() {
+        let mut processor = CommandLineProcessor::new();
+        let count = processor.add_required_typed::<i64>("count", "count", vec!["-c".to_string()]);
+        let name = processor.add_required_typed::<String>("name", "name", vec![]);
+        let path = processor.add_required_typed::<PathBuf>("path", "path", vec![]);
+
+        processor.parse_partial(&args(&["-c", "3", "--name", "x", "--path", "a/b"]));
+
+        assert_eq!(count.get(), 3);
+        assert_eq!(name.get(), "x");
+        assert_eq!(path.get(), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "required parameter count has no value")]
+    fn required_typed_get_panics_after_clear_value() {
+        let mut processor = CommandLineProcessor::new();
+        let count = processor.add_required_typed::<i64>("count", "count", vec![]);
+        processor.parse_partial(&args(&["--count", "3"]));
+        processor.clear_value("count").unwrap();
+
+        count.get();
+    }
+
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rust_cmd_arg_{}_{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn dotenv_values_fill_unset_environment_variables() {
+        let path = temp_file(
+            "fallback.env",
+            "# comment\n\nRCA_DOTENV_HOST=\"example.org\"\nRCA_DOTENV_PORT='8080'\n",
+        );
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix("RCA_DOTENV");
+        let host = processor.add_simple_parameter("host", ParameterType::String, "host");
+        let port = processor.add_simple_parameter("port", ParameterType::Integer, "port");
+        processor.load_dotenv(Some(&path)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(processor.apply_env_values().is_empty());
+
+        assert_eq!(host.to_string_value().unwrap(), "example.org");
+        assert_eq!(port.to_int_value().unwrap(), 8080);
+        assert!(host.was_set());
+    }
+
+    #[test]
+    fn environment_beats_dotenv_and_command_line_beats_both() {
+        let path = temp_file(
+            "precedence.env",
+            "RCA_PRECEDENCE_A=dotenv\nRCA_PRECEDENCE_B=dotenv\nRCA_PRECEDENCE_C=dotenv\n",
+        );
+        env::set_var("RCA_PRECEDENCE_B", "env");
+        env::set_var("RCA_PRECEDENCE_C", "env");
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix("RCA_PRECEDENCE");
+        let a = processor.add_simple_parameter("a", ParameterType::String, "a");
+        let b = processor.add_simple_parameter("b", ParameterType::String, "b");
+        let c = processor.add_simple_parameter("c", ParameterType::String, "c");
+        processor.load_dotenv(Some(&path)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(processor.apply_env_values().is_empty());
+        processor.parse_args(&args(&["--c", "cli"]), false);
+
+        assert_eq!(a.to_string_value().unwrap(), "dotenv");
+        assert_eq!(b.to_string_value().unwrap(), "env");
+        assert_eq!(c.to_string_value().unwrap(), "cli");
+    }
+
+    #[test]
+    fn load_dotenv_rejects_lines_without_equals() {
+        let path = temp_file("invalid.env", "VALID=1\nINVALID\n");
+        let mut processor = CommandLineProcessor::new();
+        let result = processor.load_dotenv(Some(&path));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result,
+            Err(format!("{}:2 expected KEY=VALUE", path.display()))
+        );
+    }
+
+    #[test]
+    fn cleared_parameter_reads_as_unset() {
+        let mut processor = CommandLineProcessor::new();
+        let level = processor.add_can_empty_parameter(
+            "level",
+            ParameterType::Integer,
+            ParameterValue::Integer(1),
+            "level",
+        );
+        processor.parse_partial(&args(&["--level", "5"]));
+        assert_eq!(level.to_int_value().unwrap(), 5);
+
+        processor.clear_value("level").unwrap();
+
+        assert_eq!(*level.get_value(), ParameterValue::Integer(1));
+        assert!(!level.was_set());
+        assert_eq!(level.occurrences(), 0);
+        assert!(processor.clear_value("missing").is_err());
+    }
+
+    #[test]
+    fn cleared_required_parameter_fails_validation() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("name", ParameterType::String, "name");
+        processor.parse_partial(&args(&["--name", "x"]));
+        assert!(processor.check_if_parse_all_arg().is_empty());
+
+        processor.clear_value("name").unwrap();
+
+        let errors = processor.check_if_parse_all_arg();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::MissingRequired);
+        assert_eq!(errors[0].parameter.as_deref(), Some("name"));
+    }
+
+    #[test]
+    fn custom_parser_builds_structured_value() {
+        let parse_size: ValueParser = Rc::new(|raw: &str| match raw.split_once('x') {
+            Some((width, height)) if !width.is_empty() && !height.is_empty() => {
+                Ok(ParameterValue::List(vec![
+                    width.to_string(),
+                    height.to_string(),
+                ]))
+            }
+            _ => Err(format!("expected WIDTHxHEIGHT, got {}", raw)),
+        });
+        let mut processor = CommandLineProcessor::new();
+        let size =
+            processor.add_simple_parameter("size", ParameterType::Custom(parse_size), "size");
+
+        processor.parse_partial(&args(&["--size", "1920x1080"]));
+        assert_eq!(size.to_list_value().unwrap(), vec!["1920", "1080"]);
+
+        processor.parse_partial(&args(&["--size", "large"]));
+        let errors = processor.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidValue);
+        assert!(errors[0]
+            .message
+            .contains("expected WIDTHxHEIGHT, got large"));
+    }
+
+    #[test]
+    fn path_base_joins_relative_paths_only() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_path_base(PathBuf::from("/srv/app"));
+        let relative = processor.add_simple_parameter("relative", ParameterType::Path, "relative");
+        let absolute = processor.add_simple_parameter("absolute", ParameterType::Path, "absolute");
+
+        processor.parse_partial(&args(&[
+            "--relative",
+            "data/in.txt",
+            "--absolute",
+            "/tmp/out",
+        ]));
+        processor.apply_path_base();
+
+        assert_eq!(
+            relative.to_path_value().unwrap(),
+            PathBuf::from("/srv/app/data/in.txt")
+        );
+        assert_eq!(absolute.to_path_value().unwrap(), PathBuf::from("/tmp/out"));
+    }
+
+    struct ServerOptions {
+        host: String,
+        port: i64,
+        verbose: bool,
+    }
+
+    impl FromCommandLine for ServerOptions {
+        fn register(processor: &mut CommandLineProcessor) {
+            processor.add_simple_parameter("host", ParameterType::String, "host");
+            processor.add_can_empty_parameter(
+                "port",
+                ParameterType::Integer,
+                ParameterValue::Integer(80),
+                "port",
+            );
+            processor.add_can_empty_parameter(
+                "verbose",
+                ParameterType::Flag,
+                ParameterValue::None,
+                "verbose",
+            );
+        }
+
+        fn from_processor(processor: &CommandLineProcessor) -> Result<Self, ParseError> {
+            let value = |name: &str| processor.get_parameter(name).unwrap();
+            Ok(ServerOptions {
+                host: value("host")
+                    .to_string_value()
+                    .map_err(|err| ParseError::for_parameter("host", &err))?,
+                port: value("port")
+                    .to_int_value()
+                    .map_err(|err| ParseError::for_parameter("port", &err))?,
+                verbose: *value("verbose").get_value() == ParameterValue::Flag,
+            })
+        }
+    }
+
+    #[test]
+    fn extract_builds_struct_from_parsed_values() {
+        let mut processor = CommandLineProcessor::new();
+        ServerOptions::register(&mut processor);
+        processor.parse_partial(&args(&["--host", "example.org", "--verbose"]));
+
+        let options: ServerOptions = processor.extract().unwrap();
+
+        assert_eq!(options.host, "example.org");
+        assert_eq!(options.port, 80);
+        assert!(options.verbose);
+    }
+
+    #[test]
+    fn extract_reports_the_failing_parameter() {
+        let mut processor = CommandLineProcessor::new();
+        ServerOptions::register(&mut processor);
+        processor.parse_partial(&args(&[]));
+
+        let error = processor.extract::<ServerOptions>().err().unwrap();
+
+        assert_eq!(error.parameter.as_deref(), Some("host"));
+    }
+
+    /// Returns the OPTIONS section of the help text, without the executable dependent usage line.
+    fn help_options(processor: &CommandLineProcessor) -> String {
+        let help_text = processor.help_text();
+        let start = help_text.find("OPTIONS\n").unwrap();
+        help_text[start..].to_string()
+    }
+
+    fn add_help_parameters(processor: &mut CommandLineProcessor) {
+        processor.set_sorted_help(true);
+        processor.set_help_width(60);
+        processor.add_parameter_detail(
+            "input",
+            ParameterType::Path,
+            false,
+            ParameterValue::None,
+            "file to read the records from; use - to read them from standard input instead",
+            vec!["-i".to_string()],
+        );
+        processor.add_can_empty_parameter(
+            "jobs",
+            ParameterType::Integer,
+            ParameterValue::Integer(4),
+            "number of worker threads",
+        );
+        processor.add_can_empty_parameter(
+            "name",
+            ParameterType::String,
+            ParameterValue::None,
+            "名称参数的说明文字 用于测试中文描述 在固定宽度下的换行 是否按字符计算",
+        );
+        processor.add_can_empty_parameter(
+            "verbose",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "print progress",
+        );
+    }
+
+    #[test]
+    fn help_columns_match_golden_file() {
+        let mut processor = CommandLineProcessor::new();
+        add_help_parameters(&mut processor);
+
+        assert_eq!(
+            help_options(&processor),
+            include_str!("../tests/golden/help_columns.txt")
+        );
+    }
+
+    #[test]
+    fn wrap_text_counts_characters() {
+        assert_eq!(wrap_text("aa bb cc", 5), vec!["aa bb", "cc"]);
+        assert_eq!(wrap_text("中文 字符 测试", 5), vec!["中文 字符", "测试"]);
+        assert_eq!(wrap_text("averylongword x", 4), vec!["averylongword", "x"]);
+    }
+
+    #[test]
+    fn value_lookup_by_short_and_slash_alias() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_detail(
+            "path",
+            ParameterType::Path,
+            false,
+            ParameterValue::None,
+            "path",
+            vec!["-p".to_string()],
+        );
+        processor.parse_partial(&args(&["-p", "in.txt"]));
+
+        let expected = ParameterValue::Path(PathBuf::from("in.txt"));
+        assert_eq!(*processor.get_value_by_alias("-p").unwrap(), expected);
+        assert_eq!(*processor.get_value_by_alias("/path").unwrap(), expected);
+        assert!(processor.get_value_by_alias("-x").is_none());
+    }
+
+    #[test]
+    fn terminated_list_collects_until_terminator() {
+        let mut processor = CommandLineProcessor::new();
+        let files = processor.add_simple_parameter("files", ParameterType::List, "files");
+        files.terminator("--end");
+        let verbose = processor.add_can_empty_parameter(
+            "verbose",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "verbose",
+        );
+
+        let leftovers = processor.parse_partial(&args(&[
+            "--files",
+            "a",
+            "--verbose",
+            "c",
+            "--end",
+            "--verbose",
+        ]));
+
+        assert_eq!(files.to_list_value().unwrap(), vec!["a", "--verbose", "c"]);
+        assert_eq!(*verbose.get_value(), ParameterValue::Flag);
+        assert!(leftovers.is_empty());
+        assert!(processor.errors().is_empty());
+    }
+
+    #[test]
+    fn terminated_list_without_terminator_is_an_error() {
+        let mut processor = CommandLineProcessor::new();
+        let files = processor.add_simple_parameter("files", ParameterType::List, "files");
+        files.terminator("--end");
+
+        processor.parse_partial(&args(&["--files", "a", "b"]));
+
+        let errors = processor.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::MissingValue);
+        assert_eq!(
+            errors[0].message,
+            "Missing terminator --end for parameter files"
+        );
+    }
+
+    #[test]
+    fn path_separators_are_normalized_in_relative_paths() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_path_separator_normalization(Some('/'));
+        let path = processor.add_simple_parameter("path", ParameterType::Path, "path");
+
+        processor.parse_partial(&args(&["--path", "a/b\\c"]));
+        processor.normalize_path_separators();
+
+        assert_eq!(path.to_path_value().unwrap(), PathBuf::from("a/b/c"));
+    }
+
+    #[test]
+    fn absolute_and_unc_paths_are_left_intact() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_path_separator_normalization(Some('/'));
+        let unix = processor.add_simple_parameter("unix", ParameterType::Path, "unix");
+        let drive = processor.add_simple_parameter("drive", ParameterType::Path, "drive");
+        let unc = processor.add_simple_parameter("unc", ParameterType::Path, "unc");
+
+        processor.parse_partial(&args(&[
+            "--unix",
+            "/var\\log",
+            "--drive",
+            "C:\\temp\\x",
+            "--unc",
+            "\\\\server\\share",
+        ]));
+        processor.normalize_path_separators();
+
+        assert_eq!(unix.to_path_value().unwrap(), PathBuf::from("/var\\log"));
+        assert_eq!(drive.to_path_value().unwrap(), PathBuf::from("C:\\temp\\x"));
+        assert_eq!(
+            unc.to_path_value().unwrap(),
+            PathBuf::from("\\\\server\\share")
+        );
+    }
+
+    #[test]
+    fn parse_partial_returns_unknown_arguments_in_order() {
+        let mut processor = CommandLineProcessor::new();
+        let config = processor.add_simple_parameter("config", ParameterType::Path, "config");
+        let verbose = processor.add_can_empty_parameter(
+            "verbose",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "verbose",
+        );
+
+        let leftovers = processor.parse_partial(&args(&[
+            "build",
+            "--config",
+            "a.toml",
+            "--release",
+            "--verbose",
+            "--help",
+            "target",
+        ]));
+
+        assert_eq!(leftovers, vec!["build", "--release", "--help", "target"]);
+        assert_eq!(config.to_path_value().unwrap(), PathBuf::from("a.toml"));
+        assert_eq!(*verbose.get_value(), ParameterValue::Flag);
+        assert!(processor.errors().is_empty());
+        assert!(!processor.abort_flag());
+    }
+
+    fn definition_processor(radix: Option<u32>) -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("path", ParameterType::Path, "path");
+        let mask = processor.add_can_empty_parameter(
+            "mask",
+            ParameterType::Integer,
+            ParameterValue::Integer(0),
+            "mask",
+        );
+        if let Some(radix) = radix {
+            mask.radix(radix);
+        }
+        processor
+    }
+
+    #[test]
+    fn definition_eq_ignores_parsed_values() {
+        let mut left = definition_processor(Some(16));
+        let right = definition_processor(Some(16));
+        left.parse_partial(&args(&["--path", "a", "--mask", "ff"]));
+
+        assert!(left.definition_eq(&right));
+    }
+
+    #[test]
+    fn definition_eq_detects_differences() {
+        let base = definition_processor(None);
+        assert!(!base.definition_eq(&definition_processor(Some(16))));
+
+        let mut extra = definition_processor(None);
+        extra.add_simple_parameter("extra", ParameterType::String, "extra");
+        assert!(!base.definition_eq(&extra));
+
+        let mut retyped = CommandLineProcessor::new();
+        retyped.add_simple_parameter("path", ParameterType::String, "path");
+        retyped.add_can_empty_parameter(
+            "mask",
+            ParameterType::Integer,
+            ParameterValue::Integer(0),
+            "mask",
+        );
+        assert!(!base.definition_eq(&retyped));
+    }
+
+    #[test]
+    fn parse_integer_detects_prefixes() {
+        assert_eq!(parse_integer("0x1F", None), Ok(31));
+        assert_eq!(parse_integer("0X1f", None), Ok(31));
+        assert_eq!(parse_integer("0o17", None), Ok(15));
+        assert_eq!(parse_integer("0b101", None), Ok(5));
+        assert_eq!(parse_integer("-0x10", None), Ok(-16));
+        assert_eq!(parse_integer("+42", None), Ok(42));
+        assert_eq!(parse_integer("042", None), Ok(42));
+        assert_eq!(parse_integer("FF", Some(16)), Ok(255));
+        assert_eq!(parse_integer("0xFF", Some(16)), Ok(255));
+    }
+
+    #[test]
+    fn parse_integer_rejects_invalid_input() {
+        assert_eq!(
+            parse_integer("0x1G", None),
+            Err("invalid hexadecimal integer 0x1G: invalid digit found in string".to_string())
+        );
+        assert!(parse_integer("12a", None).is_err());
+        assert!(parse_integer("0b102", None).is_err());
+        assert!(parse_integer("", None).is_err());
+        assert_eq!(
+            parse_integer("+-5", None),
+            Err("invalid decimal integer +-5: repeated sign".to_string())
+        );
+        assert!(parse_integer("-+5", None).is_err());
+        assert!(parse_integer("0x-5", None).is_err());
+    }
+
+    fn merged_list(strategy: MergeStrategy) -> Vec<String> {
+        let mut processor = CommandLineProcessor::new();
+        let tags = processor.add_can_empty_parameter(
+            "tags",
+            ParameterType::List,
+            ParameterValue::List(vec!["base".to_string()]),
+            "tags",
+        );
+        tags.merge_strategy(strategy);
+
+        processor.parse_partial(&args(&["--tags", "a", "--tags", "b"]));
+        tags.to_list_value().unwrap()
+    }
+
+    #[test]
+    fn merge_strategies_combine_lower_and_command_line_values() {
+        assert_eq!(merged_list(MergeStrategy::Override), vec!["a", "b"]);
+        assert_eq!(merged_list(MergeStrategy::Append), vec!["base", "a", "b"]);
+        assert_eq!(merged_list(MergeStrategy::Prepend), vec!["a", "b", "base"]);
+    }
+
+    #[test]
+    fn merge_strategy_applies_to_environment_layer() {
+        env::set_var("RCA_MERGE_TAGS", "env");
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix("RCA_MERGE");
+        let tags = processor.add_can_empty_parameter(
+            "tags",
+            ParameterType::List,
+            ParameterValue::None,
+            "tags",
+        );
+        tags.merge_strategy(MergeStrategy::Append);
+
+        assert!(processor.apply_env_values().is_empty());
+        processor.parse_args(&args(&["--tags", "cli"]), false);
+
+        assert_eq!(tags.to_list_value().unwrap(), vec!["env", "cli"]);
+    }
+
+    #[test]
+    fn help_handler_receives_full_help_text() {
+        let received = Rc::new(RefCell::new(vec![]));
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("path", ParameterType::Path, "input file");
+        let sink = Rc::clone(&received);
+        processor.set_help_handler(Box::new(move |text: &str| {
+            sink.borrow_mut().push(text.to_string())
+        }));
+
+        processor.print_help_text();
+
+        assert_eq!(*received.borrow(), vec![processor.help_text()]);
+        assert!(received.borrow()[0].contains("input file"));
+    }
+
+    fn exactly_one_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        for name in &["json", "yaml"] {
+            processor.add_can_empty_parameter(
+                name,
+                ParameterType::Flag,
+                ParameterValue::None,
+                name,
+            );
+        }
+        processor.add_exactly_one_group(&["json", "yaml"]);
+        processor
+    }
+
+    fn group_messages(processor: &CommandLineProcessor) -> Vec<String> {
+        processor
+            .check_groups()
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn exactly_one_group_counts_set_parameters() {
+        let mut processor = exactly_one_processor();
+        processor.parse_partial(&args(&[]));
+        assert_eq!(
+            group_messages(&processor),
+            vec!["one of --json, --yaml is required"]
+        );
+
+        let mut processor = exactly_one_processor();
+        processor.parse_partial(&args(&["--yaml"]));
+        assert!(group_messages(&processor).is_empty());
+
+        let mut processor = exactly_one_processor();
+        processor.parse_partial(&args(&["--json", "--yaml"]));
+        assert_eq!(
+            group_messages(&processor),
+            vec!["--json and --yaml are mutually exclusive"]
+        );
+    }
+
+    #[test]
+    fn flag_turned_off_in_environment_is_not_set() {
+        env::set_var("RCA_GROUP_JSON", "false");
+        let mut processor = exactly_one_processor();
+        processor.set_env_prefix("RCA_GROUP");
+
+        assert!(processor.apply_env_values().is_empty());
+        processor.parse_args(&args(&["--yaml"]), false);
+
+        assert!(group_messages(&processor).is_empty());
+    }
+
+    fn at_least_one_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter(
+            "file",
+            ParameterType::Path,
+            ParameterValue::None,
+            "file",
+        );
+        processor.add_can_empty_parameter(
+            "stdin",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "stdin",
+        );
+        processor.add_at_least_one_group(&["file", "stdin"]);
+        processor
+    }
+
+    #[test]
+    fn at_least_one_group_requires_a_set_parameter() {
+        let mut processor = at_least_one_processor();
+        processor.parse_partial(&args(&[]));
+        assert_eq!(
+            group_messages(&processor),
+            vec!["at least one of --file, --stdin is required"]
+        );
+
+        let mut processor = at_least_one_processor();
+        processor.parse_partial(&args(&["--stdin"]));
+        assert!(group_messages(&processor).is_empty());
+
+        let mut processor = at_least_one_processor();
+        processor.parse_partial(&args(&["--file", "a", "--stdin"]));
+        assert!(group_messages(&processor).is_empty());
+    }
+
+    #[test]
+    fn at_least_one_group_ignores_flag_turned_off_in_environment() {
+        env::set_var("RCA_AT_LEAST_STDIN", "false");
+        let mut processor = at_least_one_processor();
+        processor.set_env_prefix("RCA_AT_LEAST");
+
+        assert!(processor.apply_env_values().is_empty());
+
+        assert_eq!(
+            group_messages(&processor),
+            vec!["at least one of --file, --stdin is required"]
+        );
+    }
+
+    #[test]
+    fn split_options_separates_options_from_other_arguments() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_detail(
+            "path",
+            ParameterType::Path,
+            false,
+            ParameterValue::None,
+            "path",
+            vec!["-p".to_string()],
+        );
+        processor.add_can_empty_parameter(
+            "quiet",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "quiet",
+        );
+        let files = processor.add_simple_parameter("files", ParameterType::List, "files");
+        files.terminator(";");
+
+        let (options, others) = processor.split_options(&args(&[
+            "run", "-p", "x", "--other", "--quiet", "--files", "a", "b", ";", "tail",
+        ]));
+
+        let pairs: Vec<(&str, &str)> = options
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("path", "x"), ("quiet", ""), ("files", "a"), ("files", "b")]
+        );
+        assert_eq!(others, vec!["run", "--other", "tail"]);
+        assert!(processor
+            .get_parameter("path")
+            .unwrap()
+            .get_value()
+            .is_none());
+    }
+
+    #[test]
+    fn value_file_reference_reads_trimmed_contents() {
+        let path = temp_file("value.txt", "  secret token\n");
+        let mut processor = CommandLineProcessor::new();
+        let token = processor.add_simple_parameter("token", ParameterType::String, "token");
+
+        processor.parse_partial(&args(&["--token", &format!("@{}", path.display())]));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(token.to_string_value().unwrap(), "secret token");
+    }
+
+    #[test]
+    fn double_at_escapes_a_literal_at() {
+        assert_eq!(
+            read_value_file("@@handle".to_string()),
+            Ok("@handle".to_string())
+        );
+        assert_eq!(
+            read_value_file("plain".to_string()),
+            Ok("plain".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_value_file_is_an_invalid_value() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("token", ParameterType::String, "token");
+
+        processor.parse_partial(&args(&["--token", "@/nonexistent/rust_cmd_arg/token"]));
+
+        let errors = processor.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidValue);
+        assert!(errors[0]
+            .message
+            .starts_with("Unable to read value file /nonexistent/rust_cmd_arg/token"));
+    }
+
+    #[test]
+    fn occurrences_count_every_alias() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_detail(
+            "verbose",
+            ParameterType::Flag,
+            true,
+            ParameterValue::None,
+            "verbose",
+            vec!["-v".to_string()],
+        );
+        processor.add_can_empty_parameter(
+            "once",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "once",
+        );
+        processor.add_can_empty_parameter(
+            "level",
+            ParameterType::Integer,
+            ParameterValue::None,
+            "level",
+        );
+
+        processor.parse_partial(&args(&[
+            "-v",
+            "--verbose",
+            "--once",
+            "--level",
+            "1",
+            "--level",
+            "2",
+        ]));
+
+        assert_eq!(processor.occurrences("once"), 1);
+        assert_eq!(processor.occurrences("verbose"), 2);
+        assert_eq!(processor.occurrences("level"), 2);
+        assert_eq!(
+            processor
+                .get_parameter("level")
+                .unwrap()
+                .to_int_value()
+                .unwrap(),
+            2
+        );
+        assert_eq!(processor.occurrences("missing"), 0);
+    }
+
+    #[test]
+    fn interpolate_env_expands_variables() {
+        env::set_var("RCA_INTERPOLATE_HOME", "/home/user");
+        env::remove_var("RCA_INTERPOLATE_UNDEFINED");
+
+        assert_eq!(interpolate_env("$RCA_INTERPOLATE_HOME/x"), "/home/user/x");
+        assert_eq!(interpolate_env("${RCA_INTERPOLATE_HOME}x"), "/home/userx");
+        assert_eq!(interpolate_env("a${RCA_INTERPOLATE_UNDEFINED}b"), "ab");
+        assert_eq!(interpolate_env("cost $$5"), "cost $5");
+        assert_eq!(interpolate_env("$ and ${open"), "$ and ${open");
+    }
+
+    #[test]
+    fn env_interpolation_only_applies_when_enabled() {
+        env::set_var("RCA_INTERPOLATE_DIR", "/data");
+        let mut processor = CommandLineProcessor::new();
+        let path = processor.add_simple_parameter("path", ParameterType::Path, "path");
+
+        processor.parse_partial(&args(&["--path", "$RCA_INTERPOLATE_DIR/in"]));
+        processor.interpolate_env_values();
+        assert_eq!(
+            path.to_path_value().unwrap(),
+            PathBuf::from("$RCA_INTERPOLATE_DIR/in")
+        );
+
+        processor.set_env_interpolation(true);
+        processor.interpolate_env_values();
+        assert_eq!(path.to_path_value().unwrap(), PathBuf::from("/data/in"));
+    }
+
+    fn experimental_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_experimental_gate("unstable");
+        processor.add_can_empty_parameter(
+            "unstable",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "unstable",
+        );
+        let turbo = processor.add_can_empty_parameter(
+            "turbo",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "turbo",
+        );
+        turbo.experimental(true);
+        processor
+    }
+
+    #[test]
+    fn experimental_parameter_requires_gate() {
+        let mut processor = experimental_processor();
+        processor.parse_partial(&args(&["--turbo"]));
+        let errors = processor.check_experimental();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "--turbo is experimental; pass --unstable to use it"
+        );
+
+        let mut processor = experimental_processor();
+        processor.parse_partial(&args(&["--unstable", "--turbo"]));
+        assert!(processor.check_experimental().is_empty());
+
+        let mut processor = experimental_processor();
+        processor.parse_partial(&args(&[]));
+        assert!(processor.check_experimental().is_empty());
+    }
+
+    #[test]
+    fn experimental_flag_turned_off_in_environment_is_not_used() {
+        env::set_var("RCA_EXPERIMENTAL_TURBO", "false");
+        let mut processor = experimental_processor();
+        processor.set_env_prefix("RCA_EXPERIMENTAL");
+
+        assert!(processor.apply_env_values().is_empty());
+
+        assert!(processor.check_experimental().is_empty());
+    }
+
+    #[test]
+    fn version_goes_to_stdout_without_help() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_version_text("tool 1.2.3");
+
+        processor.parse_args(&args(&["--version"]), false);
+        let report = processor.exit_report(-1);
+
+        assert_eq!(report.stdout, "tool 1.2.3\n");
+        assert_eq!(report.stderr, "");
+        assert!(!report.help);
+        assert_eq!(report.code, 0);
+    }
+
+    #[test]
+    fn errors_and_help_go_to_stderr() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_version_text("tool 1.2.3");
+
+        processor.parse_args(&args(&["--unknown"]), false);
+        let report = processor.exit_report(-1);
+
+        assert_eq!(report.stdout, "");
+        assert_eq!(report.stderr, "error: Unknown parameter: --unknown\n\n");
+        assert!(report.help);
+        assert_eq!(report.code, -1);
+    }
+
+    #[test]
+    fn help_request_goes_to_stderr() {
+        let mut processor = CommandLineProcessor::new();
+
+        processor.parse_args(&args(&["--help"]), false);
+        let report = processor.exit_report(-1);
+
+        assert!(processor.abort_flag());
+        assert_eq!(report.stdout, "");
+        assert_eq!(report.stderr, "");
+        assert!(report.help);
+    }
+
+    #[test]
+    fn add_alias_rejects_taken_aliases() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("host", ParameterType::String, "host");
+        processor.add_parameter_detail(
+            "port",
+            ParameterType::Integer,
+            true,
+            ParameterValue::None,
+            "port",
+            vec!["-p".to_string()],
+        );
+
+        assert_eq!(
+            processor.add_alias("host", "-p"),
+            Err("alias -p is already used by parameter port".to_string())
+        );
+        assert_eq!(
+            processor.add_alias("host", "--help"),
+            Err("alias --help is already used for help".to_string())
+        );
+        assert_eq!(
+            processor.add_alias("host", "--v"),
+            Err("alias --v is already used for version".to_string())
+        );
+        assert_eq!(
+            processor.add_alias("missing", "-m"),
+            Err("Unknown parameter: missing".to_string())
+        );
+    }
+
+    #[test]
+    fn added_alias_matches_on_the_command_line() {
+        let mut processor = CommandLineProcessor::new();
+        let host = processor.add_simple_parameter("host", ParameterType::String, "host");
+        processor.add_alias("host", "--server").unwrap();
+
+        processor.parse_partial(&args(&["--server", "example.org"]));
+
+        assert_eq!(host.to_string_value().unwrap(), "example.org");
+    }
+
+    #[test]
+    fn export_env_quotes_values_and_skips_sensitive_ones() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("message", ParameterType::String, "message");
+        processor.add_simple_parameter("quote", ParameterType::String, "quote");
+        let password =
+            processor.add_simple_parameter("password", ParameterType::String, "password");
+        password.sensitive(true);
+        processor.add_can_empty_parameter(
+            "level",
+            ParameterType::Integer,
+            ParameterValue::Integer(3),
+            "level",
+        );
+
+        processor.parse_partial(&args(&[
+            "--message",
+            "hello world",
+            "--quote",
+            "it's",
+            "--password",
+            "hunter2",
+        ]));
+
+        assert_eq!(
+            processor.export_env("APP"),
+            "export APP_MESSAGE='hello world'\nexport APP_QUOTE='it'\\''s'\n"
+        );
+    }
+
+    #[test]
+    fn env_var_name_is_a_valid_shell_name() {
+        assert_eq!(env_var_name("APP", "dry-run"), "APP_DRY_RUN");
+        assert_eq!(env_var_name("app", "log.level"), "APP_LOG_LEVEL");
+        assert_eq!(env_var_name("", "2fa code"), "_2FA_CODE");
+    }
+
+    #[test]
+    fn trailing_words_are_joined_into_one_string() {
+        let mut processor = CommandLineProcessor::new();
+        let message = processor.add_can_empty_parameter(
+            "message",
+            ParameterType::TrailingText,
+            ParameterValue::None,
+            "message",
+        );
+        let amend = processor.add_can_empty_parameter(
+            "amend",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "amend",
+        );
+
+        processor.parse_args(&args(&["fix", "the", "--amend", "parser"]), false);
+
+        assert_eq!(message.to_string_value().unwrap(), "fix the parser");
+        assert_eq!(*amend.get_value(), ParameterValue::Flag);
+        assert!(processor.errors().is_empty());
+    }
+
+    #[test]
+    fn no_trailing_words_respects_allow_empty() {
+        let mut processor = CommandLineProcessor::new();
+        let message = processor.add_can_empty_parameter(
+            "message",
+            ParameterType::TrailingText,
+            ParameterValue::None,
+            "message",
+        );
+        processor.parse_args(&args(&[]), false);
+        assert!(message.get_value().is_none());
+        assert!(processor.check_if_parse_all_arg().is_empty());
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("message", ParameterType::TrailingText, "message");
+        processor.parse_args(&args(&[]), false);
+        let errors = processor.check_if_parse_all_arg();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::MissingRequired);
+    }
+
+    #[test]
+    fn parse_partial_returns_free_words_instead_of_trailing_text() {
+        let mut processor = CommandLineProcessor::new();
+        let message = processor.add_can_empty_parameter(
+            "message",
+            ParameterType::TrailingText,
+            ParameterValue::None,
+            "message",
+        );
+
+        let leftovers = processor.parse_partial(&args(&["free", "--message", "words"]));
+
+        assert_eq!(leftovers, vec!["free"]);
+        assert_eq!(message.to_string_value().unwrap(), "words");
+    }
+
+    fn order_errors(arguments: &[&str]) -> Vec<String> {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter(
+            "input",
+            ParameterType::Path,
+            ParameterValue::None,
+            "input",
+        );
+        processor.add_can_empty_parameter(
+            "filter",
+            ParameterType::String,
+            ParameterValue::None,
+            "filter",
+        );
+        processor.add_order_constraint("input", "filter");
+
+        processor.parse_partial(&args(arguments));
+        processor
+            .check_order()
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn order_constraint_accepts_correct_order() {
+        assert!(order_errors(&["--input", "a", "--filter", "x"]).is_empty());
+    }
+
+    #[test]
+    fn order_constraint_rejects_wrong_order() {
+        assert_eq!(
+            order_errors(&["--filter", "x", "--input", "a"]),
+            vec!["--input must appear before --filter"]
+        );
+    }
+
+    #[test]
+    fn order_constraint_ignores_missing_parameter() {
+        assert!(order_errors(&["--filter", "x"]).is_empty());
+        assert!(order_errors(&["--input", "a"]).is_empty());
+    }
+
+    #[test]
+    fn with_defaults_applies_modern_conventions() {
+        let processor = CommandLineProcessor::with_defaults();
+
+        assert_eq!(processor.help_aliases, vec!["-h", "--help"]);
+        assert_eq!(processor.version_aliases, vec!["-V", "--version"]);
+        assert!(processor.sorted_help);
+        assert_eq!(processor.help_width, CommandLineProcessor::new().help_width);
+        assert_eq!(CommandLineProcessor::default().help_width, 80);
+    }
+
+    #[test]
+    fn with_defaults_handles_help_and_version_without_exiting() {
+        let mut processor = CommandLineProcessor::with_defaults();
+        processor.set_version_text("tool 1.0");
+
+        assert_eq!(
+            processor.try_parse(&args(&["-h"])),
+            Ok(ParseOutcome::HelpRequested)
+        );
+        assert_eq!(
+            processor.try_parse(&args(&["-V"])),
+            Ok(ParseOutcome::VersionRequested("tool 1.0".to_string()))
+        );
+        let errors = processor.try_parse(&args(&["--h"])).unwrap_err();
+        assert_eq!(errors[0].kind, ParseErrorKind::UnknownParameter);
+    }
+
+    #[test]
+    #[should_panic(expected = "alias -h of parameter host is already used for help")]
+    fn parameter_alias_colliding_with_help_panics() {
+        let mut processor = CommandLineProcessor::with_defaults();
+        processor.add_parameter_detail(
+            "host",
+            ParameterType::String,
+            false,
+            ParameterValue::None,
+            "host",
+            vec!["-h".to_string()],
+        );
+    }
+
+    #[test]
+    fn help_aliases_can_be_changed_to_free_an_alias() {
+        let mut processor = CommandLineProcessor::with_defaults();
+        processor.set_help_aliases(&["--help"]);
+        let host = processor.add_parameter_detail(
+            "host",
+            ParameterType::String,
+            false,
+            ParameterValue::None,
+            "host",
+            vec!["-h".to_string()],
+        );
+
+        assert_eq!(
+            processor.try_parse(&args(&["-h", "x"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert_eq!(host.to_string_value().unwrap(), "x");
+    }
+
+    #[test]
+    #[should_panic(expected = "alias -h is already used by parameter host")]
+    fn help_alias_colliding_with_parameter_panics() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_detail(
+            "host",
+            ParameterType::String,
+            false,
+            ParameterValue::None,
+            "host",
+            vec!["-h".to_string()],
+        );
+        processor.set_help_aliases(&["-h", "--help"]);
+    }
+
+    #[test]
+    fn generated_alias_colliding_with_version_is_skipped() {
+        let mut processor = CommandLineProcessor::new();
+        let verbose =
+            processor.add_can_empty_parameter("v", ParameterType::Flag, ParameterValue::None, "v");
+
+        assert_eq!(*verbose.aliases.borrow(), vec!["/v"]);
+    }
+
+    #[test]
+    fn description_provider_is_rendered_on_each_help() {
+        let mut processor = CommandLineProcessor::new();
+        let jobs = Rc::new(Cell::new(4));
+        let provided = Rc::clone(&jobs);
+        processor
+            .add_can_empty_parameter(
+                "jobs",
+                ParameterType::Integer,
+                ParameterValue::None,
+                "static text",
+            )
+            .description_provider(Box::new(move || {
+                format!("defaults to {} jobs", provided.get())
+            }));
+
+        let help_text = processor.help_text();
+        assert!(help_text.contains("defaults to 4 jobs"));
+        assert!(!help_text.contains("static text"));
+
+        jobs.set(16);
+        assert!(processor.help_text().contains("defaults to 16 jobs"));
+    }
+
+    #[test]
+    fn option_value_is_taken_literally_even_if_it_is_help() {
+        let mut processor = CommandLineProcessor::new();
+        let grep = processor.add_can_empty_parameter(
+            "grep",
+            ParameterType::String,
+            ParameterValue::None,
+            "pattern",
+        );
+        processor.add_can_empty_parameter(
+            "verbose",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "verbose",
+        );
+
+        assert_eq!(
+            processor.try_parse(&args(&["--grep", "--help"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert_eq!(grep.to_string_value().unwrap(), "--help");
+        assert!(!processor.help_requested);
+
+        assert_eq!(
+            processor.try_parse(&args(&["--grep", "--version"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert_eq!(grep.to_string_value().unwrap(), "--version");
+        assert!(!processor.version_requested);
+    }
+
+    #[test]
+    fn help_after_a_complete_option_is_still_handled() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter(
+            "grep",
+            ParameterType::String,
+            ParameterValue::None,
+            "pattern",
+        );
+
+        assert_eq!(
+            processor.try_parse(&args(&["--grep", "x", "--help"])),
+            Ok(ParseOutcome::HelpRequested)
+        );
+    }
+
+    #[test]
+    fn every_invalid_environment_value_is_reported() {
+        env::set_var("RCA_ENVERRORS_JOBS", "many");
+        env::set_var("RCA_ENVERRORS_PORT", "http");
+        env::set_var("RCA_ENVERRORS_HOST", "example.org");
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix("RCA_ENVERRORS");
+        processor.set_error_report_format(ErrorReportFormat::Json);
+        let host = processor.add_simple_parameter("host", ParameterType::String, "host");
+        processor.add_simple_parameter("jobs", ParameterType::Integer, "jobs");
+        processor.add_simple_parameter("port", ParameterType::Integer, "port");
+
+        let errors = processor.try_parse(&args(&[])).unwrap_err();
+
+        assert_eq!(host.to_string_value().unwrap(), "example.org");
+        let names: Vec<_> = errors
+            .iter()
+            .map(|error| error.parameter.as_deref())
+            .collect();
+        assert_eq!(names, vec![Some("jobs"), Some("port")]);
+        let report = processor.exit_report(-1);
+        assert_eq!(
+            report.stderr,
+            format!("[{},{}]\n", errors[0].to_json(), errors[1].to_json())
+        );
+        assert!(report.stderr.contains("\"argument\":\"many\""));
+        assert!(report.stderr.contains("\"argument\":\"http\""));
+    }
+
+    #[test]
+    fn radix_default_is_shown_in_that_radix() {
+        let mut processor = CommandLineProcessor::new();
+        processor
+            .add_can_empty_parameter(
+                "mask",
+                ParameterType::Integer,
+                ParameterValue::Integer(255),
+                "mask",
+            )
+            .radix(16);
+        processor
+            .add_can_empty_parameter(
+                "mode",
+                ParameterType::Integer,
+                ParameterValue::Integer(0o755),
+                "mode",
+            )
+            .radix(8);
+        processor.add_can_empty_parameter(
+            "count",
+            ParameterType::Integer,
+            ParameterValue::Integer(255),
+            "count",
+        );
+
+        let help_text = processor.help_text();
+
+        assert!(help_text.contains("0xFF"));
+        assert!(help_text.contains("0o755"));
+        assert!(help_text.contains("255"));
+        assert_eq!(format_integer(-255, 16), "-0xFF");
+        assert_eq!(format_integer(5, 2), "0b101");
+        assert_eq!(format_integer(35, 36), "Z");
+    }
+
+    fn relation_processor(comparison: Comparison) -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter(
+            "min",
+            ParameterType::Integer,
+            ParameterValue::None,
+            "min",
+        );
+        processor.add_can_empty_parameter(
+            "max",
+            ParameterType::Integer,
+            ParameterValue::Integer(5),
+            "max",
+        );
+        processor.add_relation(Relation::new("min", comparison, "max"));
+        processor
+    }
+
+    #[test]
+    fn satisfied_relation_passes() {
+        let mut processor = relation_processor(Comparison::LessOrEqual);
+
+        assert_eq!(
+            processor.try_parse(&args(&["--min", "5"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert_eq!(
+            processor.try_parse(&args(&["--min", "1", "--max", "0x10"])),
+            Ok(ParseOutcome::Parsed)
+        );
+    }
+
+    #[test]
+    fn violated_relation_is_reported() {
+        let mut processor = relation_processor(Comparison::LessOrEqual);
+
+        let errors = processor.try_parse(&args(&["--min", "10"])).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::Constraint);
+        assert_eq!(errors[0].parameter.as_deref(), Some("min"));
+        assert_eq!(errors[0].message, "--min (10) must be <= --max (5)");
+    }
+
+    #[test]
+    fn relation_with_unset_side_is_skipped() {
+        let mut processor = relation_processor(Comparison::Greater);
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+    }
+
+    #[test]
+    fn each_comparison_is_checked() {
+        let cases = [
+            (Comparison::Less, "4", "5"),
+            (Comparison::Equal, "5", "4"),
+            (Comparison::NotEqual, "4", "5"),
+            (Comparison::GreaterOrEqual, "5", "4"),
+            (Comparison::Greater, "6", "5"),
+        ];
+        for (comparison, holds, fails) in cases.iter() {
+            let mut processor = relation_processor(*comparison);
+            assert_eq!(
+                processor.try_parse(&args(&["--min", holds])),
+                Ok(ParseOutcome::Parsed)
+            );
+            assert!(processor.try_parse(&args(&["--min", fails])).is_err());
+        }
+    }
+
+    fn inline_value_processor(allow_empty: bool) -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_detail(
+            "path",
+            ParameterType::String,
+            allow_empty,
+            ParameterValue::None,
+            "path",
+            vec!["-p".to_string()],
+        );
+        processor
+    }
+
+    #[test]
+    fn inline_value_works_with_every_alias_form() {
+        for option in ["-p=./x", "/path=./x", "--path=./x"].iter() {
+            let mut processor = inline_value_processor(false);
+
+            assert_eq!(
+                processor.try_parse(&args(&[option])),
+                Ok(ParseOutcome::Parsed)
+            );
+            assert_eq!(
+                processor
+                    .get_parameter("path")
+                    .unwrap()
+                    .to_string_value()
+                    .unwrap(),
+                "./x"
+            );
+
+            let (options, others) = processor.split_options(&args(&[option, "rest"]));
+            assert_eq!(options, vec![("path".to_string(), "./x".to_string())]);
+            assert_eq!(others, vec!["rest"]);
+        }
+    }
+
+    #[test]
+    fn attached_value_without_separator_is_not_split() {
+        let mut processor = inline_value_processor(false);
+
+        let errors = processor.try_parse(&args(&["-p./x"])).unwrap_err();
+        assert_eq!(errors[0].kind, ParseErrorKind::UnknownParameter);
+        let (options, others) = processor.split_options(&args(&["-p./x"]));
+        assert!(options.is_empty());
+        assert_eq!(others, vec!["-p./x"]);
+    }
+
+    #[test]
+    fn empty_inline_value_respects_allow_empty() {
+        let mut processor = inline_value_processor(true);
+        assert_eq!(
+            processor.try_parse(&args(&["-p="])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert!(processor
+            .get_parameter("path")
+            .unwrap()
+            .get_value()
+            .is_none());
+        assert_eq!(
+            processor.split_options(&args(&["-p=", "rest"])).0,
+            vec![("path".to_string(), String::new())]
+        );
+
+        let mut processor = inline_value_processor(false);
+        let errors = processor.try_parse(&args(&["-p="])).unwrap_err();
+        assert_eq!(errors[0].kind, ParseErrorKind::MissingValue);
+    }
+
+    #[test]
+    fn version_provider_is_only_called_on_demand() {
+        let calls = Rc::new(Cell::new(0));
+        let counted = Rc::clone(&calls);
+        let mut processor = CommandLineProcessor::new();
+        processor.set_version_text("static");
+        processor.set_version_provider(Box::new(move || {
+            counted.set(counted.get() + 1);
+            "tool 2.0 (built today)".to_string()
+        }));
+        processor.add_can_empty_parameter(
+            "quiet",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "quiet",
+        );
+
+        assert_eq!(
+            processor.try_parse(&args(&["--quiet"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert!(processor.try_parse(&args(&["--help"])).is_ok());
+        assert_eq!(calls.get(), 0);
+
+        assert_eq!(
+            processor.try_parse(&args(&["--version"])),
+            Ok(ParseOutcome::VersionRequested(
+                "tool 2.0 (built today)".to_string()
+            ))
+        );
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn parse_value_converts_each_type() {
+        assert_eq!(
+            ParameterType::Flag.parse_value("true"),
+            Ok(ParameterValue::Flag)
+        );
+        assert_eq!(
+            ParameterType::Flag.parse_value("false"),
+            Ok(ParameterValue::None)
+        );
+        assert_eq!(
+            ParameterType::Integer.parse_value("-12"),
+            Ok(ParameterValue::Integer(-12))
+        );
+        assert_eq!(
+            ParameterType::Integer.parse_value("0x1F"),
+            Ok(ParameterValue::Integer(31))
+        );
+        assert_eq!(
+            ParameterType::Float.parse_value("2.5"),
+            Ok(ParameterValue::Float(2.5))
+        );
+        assert_eq!(
+            ParameterType::Path.parse_value("a/b"),
+            Ok(ParameterValue::Path(PathBuf::from("a/b")))
+        );
+        assert_eq!(
+            ParameterType::String.parse_value(" x "),
+            Ok(ParameterValue::String(" x ".to_string()))
+        );
+        assert_eq!(
+            ParameterType::TrailingText.parse_value("a b"),
+            Ok(ParameterValue::String("a b".to_string()))
+        );
+        assert_eq!(
+            ParameterType::Bool.parse_value("false"),
+            Ok(ParameterValue::Bool(false))
+        );
+        assert_eq!(
+            ParameterType::List.parse_value("a, b,c"),
+            Ok(ParameterValue::List(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string()
+            ]))
+        );
+        let custom = ParameterType::Custom(Rc::new(|raw: &str| {
+            Ok(ParameterValue::Integer(raw.len() as i64))
+        }));
+        assert_eq!(custom.parse_value("abc"), Ok(ParameterValue::Integer(3)));
+    }
+
+    #[test]
+    fn parse_value_reports_invalid_input() {
+        let flag = ParameterType::Flag.parse_value("yes").unwrap_err();
+        assert!(flag.starts_with("Unable to convert yes to flag\n"));
+        let integer = ParameterType::Integer.parse_value("12a").unwrap_err();
+        assert!(integer.contains("12a"));
+        let float = ParameterType::Float.parse_value("x").unwrap_err();
+        assert!(float.starts_with("Unable to convert x to float\n"));
+        let boolean = ParameterType::Bool.parse_value("1").unwrap_err();
+        assert!(boolean.starts_with("Unable to convert 1 to bool\n"));
+        let custom = ParameterType::Custom(Rc::new(|raw: &str| Err(format!("bad {}", raw))));
+        assert_eq!(custom.parse_value("x"), Err("bad x".to_string()));
+    }
+
+    #[test]
+    fn parameter_parse_value_applies_radix() {
+        let mut processor = CommandLineProcessor::new();
+        let mask = processor.add_simple_parameter("mask", ParameterType::Integer, "mask");
+        mask.radix(16);
+
+        assert_eq!(mask.parse_value("ff"), Ok(ParameterValue::Integer(255)));
+        assert!(mask.parse_value("fg").is_err());
+        assert_eq!(
+            ParameterType::Integer.parse_value_in_radix("11", Some(2)),
+            Ok(ParameterValue::Integer(3))
+        );
+    }
+
+    #[test]
+    fn command_line_and_environment_lists_split_alike() {
+        env::set_var("RCA_LISTSPLIT_TAGS", "a,b");
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix("RCA_LISTSPLIT");
+        let tags = processor.add_simple_parameter("tags", ParameterType::List, "tags");
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+        let from_env = tags.get_value().clone();
+        assert_eq!(
+            processor.try_parse(&args(&["--tags", "a,b"])),
+            Ok(ParseOutcome::Parsed)
+        );
+
+        assert_eq!(
+            from_env,
+            ParameterValue::List(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(tags.get_value().clone(), from_env);
+    }
+
+    fn implication_processor(env_prefix: &str) -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_env_prefix(env_prefix);
+        for name in ["quiet", "no-progress", "no-color"].iter() {
+            processor.add_can_empty_parameter(
+                name,
+                ParameterType::Flag,
+                ParameterValue::None,
+                name,
+            );
+        }
+        processor.add_implication("quiet", &["no-progress"]);
+        processor.add_implication("no-progress", &["no-color"]);
+        processor
+    }
+
+    fn is_flag_on(processor: &CommandLineProcessor, name: &str) -> bool {
+        *processor.get_parameter(name).unwrap().get_value() == ParameterValue::Flag
+    }
+
+    #[test]
+    fn implication_sets_implied_flags() {
+        let mut processor = implication_processor("RCA_IMPLIED");
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+        assert!(!is_flag_on(&processor, "no-progress"));
+
+        assert_eq!(
+            processor.try_parse(&args(&["--quiet"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert!(is_flag_on(&processor, "no-progress"));
+        assert!(is_flag_on(&processor, "no-color"));
+        assert!(!processor.get_parameter("no-progress").unwrap().was_set());
+    }
+
+    #[test]
+    fn explicit_value_overrides_implication() {
+        env::set_var("RCA_IMPLIES_NO_PROGRESS", "false");
+        let mut processor = implication_processor("RCA_IMPLIES");
+
+        let outcome = processor.try_parse(&args(&["--quiet"]));
+        env::remove_var("RCA_IMPLIES_NO_PROGRESS");
+
+        assert_eq!(outcome, Ok(ParseOutcome::Parsed));
+        assert!(is_flag_on(&processor, "quiet"));
+        assert!(!is_flag_on(&processor, "no-progress"));
+        assert!(processor.get_parameter("no-progress").unwrap().was_set());
+        assert!(!is_flag_on(&processor, "no-color"));
+    }
+
+    fn collected_list(dedup: bool, sorted: bool, values: &[&str]) -> Vec<String> {
+        let mut processor = CommandLineProcessor::new();
+        let tags = processor.add_simple_parameter("tags", ParameterType::List, "tags");
+        tags.dedup(dedup).sorted(sorted);
+        let mut arguments = vec![];
+        for value in values {
+            arguments.push("--tags");
+            arguments.push(value);
+        }
+
+        assert_eq!(
+            processor.try_parse(&args(&arguments)),
+            Ok(ParseOutcome::Parsed)
+        );
+        tags.to_list_value().unwrap()
+    }
+
+    #[test]
+    fn list_dedup_keeps_first_occurrence_order() {
+        assert_eq!(
+            collected_list(true, false, &["b", "a", "b", "c", "a"]),
+            vec!["b", "a", "c"]
+        );
+    }
+
+    #[test]
+    fn list_sort_keeps_duplicates() {
+        assert_eq!(
+            collected_list(false, true, &["b", "a", "b"]),
+            vec!["a", "b", "b"]
+        );
+    }
+
+    #[test]
+    fn list_dedup_and_sort_together() {
+        assert_eq!(
+            collected_list(true, true, &["c", "a", "c", "b"]),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(
+            collected_list(false, false, &["c", "a", "c"]),
+            vec!["c", "a", "c"]
+        );
+    }
+
+    #[test]
+    fn unchanged_list_default_is_used_as_given() {
+        let mut processor = CommandLineProcessor::new();
+        let default = ParameterValue::List(vec!["b".to_string(), "a".to_string(), "b".to_string()]);
+        let tags =
+            processor.add_can_empty_parameter("tags", ParameterType::List, default.clone(), "tags");
+        tags.dedup(true).sorted(true);
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+        assert_eq!(*tags.get_value(), default);
+    }
+
+    fn presence_flag(env_name: &str) -> bool {
+        let mut processor = CommandLineProcessor::new();
+        processor
+            .add_can_empty_parameter("debug", ParameterType::Flag, ParameterValue::None, "debug")
+            .env_presence(env_name);
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+        processor.is_parameter_set("debug")
+    }
+
+    #[test]
+    fn env_presence_with_variable_set_enables_flag() {
+        env::set_var("RCA_PRESENCE_SET", "no");
+
+        assert!(presence_flag("RCA_PRESENCE_SET"));
+    }
+
+    #[test]
+    fn env_presence_with_empty_variable_counts_as_unset() {
+        env::set_var("RCA_PRESENCE_EMPTY", "");
+
+        assert!(!presence_flag("RCA_PRESENCE_EMPTY"));
+    }
+
+    #[test]
+    fn env_presence_with_unset_variable_leaves_flag_off() {
+        env::remove_var("RCA_PRESENCE_UNSET");
+
+        assert!(!presence_flag("RCA_PRESENCE_UNSET"));
+    }
+
+    #[test]
+    fn error_block_numbers_two_errors() {
+        let errors = vec![
+            ParseError::for_parameter("path", "No value passed for parameter path"),
+            ParseError::from("at least one of --a, --b is required".to_string()),
+        ];
+
+        assert_eq!(
+            format_error_block(&errors),
+            "error: 2 problems found\n  1. No value passed for parameter path\n  2. at least one of --a, --b is required"
+        );
+    }
+
+    #[test]
+    fn error_block_indents_multi_line_messages() {
+        let errors = vec![
+            ParseError::from("first".to_string()),
+            ParseError::from("Unable to convert parameter port\ninvalid digit".to_string()),
+            ParseError::from("third".to_string()),
+        ];
+
+        assert_eq!(
+            format_error_block(&errors),
+            "error: 3 problems found\n  1. first\n  2. Unable to convert parameter port\n     invalid digit\n  3. third"
+        );
+        assert_eq!(format_error_block(&errors[..1]), "error: first");
+        assert_eq!(format_error_block(&[]), "");
+    }
+
+    #[test]
+    fn error_block_aligns_double_digit_numbers() {
+        let errors: Vec<ParseError> = (1..=10)
+            .map(|index| ParseError::from(format!("problem {}\ndetail", index)))
+            .collect();
+
+        let block = format_error_block(&errors);
+
+        assert!(block.starts_with("error: 10 problems found\n   1. problem 1\n      detail\n"));
+        assert!(block.ends_with("\n  10. problem 10\n      detail"));
+    }
+
+    #[test]
+    fn timing_is_recorded_only_when_enabled() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter(
+            "jobs",
+            ParameterType::Integer,
+            ParameterValue::None,
+            "jobs",
+        );
+
+        assert_eq!(
+            processor.try_parse(&args(&["--jobs", "2"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert_eq!(processor.parse_timing(), ParseTiming::default());
+
+        processor.set_timing_enabled(true);
+        assert_eq!(
+            processor.try_parse(&args(&["--jobs", "2"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        let timing = processor.parse_timing();
+        assert!(timing.total() > Duration::from_nanos(0));
+        assert_eq!(
+            timing.total(),
+            timing.environment + timing.arguments + timing.post_processing + timing.validation
+        );
+
+        processor.parse_partial(&args(&["--jobs", "3"]));
+        let timing = processor.parse_timing();
+        assert_eq!(timing.total(), timing.arguments);
+    }
+
+    #[test]
+    fn only_letter_drives_count_as_absolute() {
+        assert!(has_drive_prefix("C:\\temp"));
+        assert!(has_drive_prefix("c:/temp"));
+        assert!(has_drive_prefix("D:"));
+        assert!(!has_drive_prefix("x:y\\z"));
+        assert!(!has_drive_prefix("1:\\y"));
+        assert!(!has_drive_prefix(":\\y"));
+        assert!(!has_drive_prefix("C"));
+
+        let mut processor = CommandLineProcessor::new();
+        processor.set_path_separator_normalization(Some('/'));
+        let name = processor.add_simple_parameter("name", ParameterType::Path, "name");
+        let digit = processor.add_simple_parameter("digit", ParameterType::Path, "digit");
+
+        processor.parse_partial(&args(&["--name", "x:y\\z", "--digit", "1:\\y\\z"]));
+        processor.normalize_path_separators();
+
+        assert_eq!(name.to_path_value().unwrap(), PathBuf::from("x:y/z"));
+        assert_eq!(digit.to_path_value().unwrap(), PathBuf::from("1:/y/z"));
+    }
+
+    fn trailing_text_errors(arguments: &[&str]) -> Result<String, Vec<ParseError>> {
+        let mut processor = CommandLineProcessor::new();
+        let message = processor.add_can_empty_parameter(
+            "message",
+            ParameterType::TrailingText,
+            ParameterValue::None,
+            "message",
+        );
+        processor.add_can_empty_parameter(
+            "verbose",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "verbose",
+        );
+
+        processor.try_parse(&args(arguments))?;
+        let text = message.to_string_value().unwrap();
+        Ok(text)
+    }
+
+    #[test]
+    fn unknown_slash_option_is_not_trailing_text() {
+        let errors = trailing_text_errors(&["/verbsoe", "hi"]).unwrap_err();
+
+        assert_eq!(errors[0].kind, ParseErrorKind::UnknownParameter);
+        assert_eq!(errors[0].argument.as_deref(), Some("/verbsoe"));
+    }
+
+    #[test]
+    fn slash_paths_are_trailing_text() {
+        assert_eq!(
+            trailing_text_errors(&["copy", "/tmp/x", "/verbose"]),
+            Ok("copy /tmp/x".to_string())
+        );
+    }
+
+    #[test]
+    fn option_like_words_are_recognized() {
+        assert!(looks_like_option("-x"));
+        assert!(looks_like_option("--name"));
+        assert!(looks_like_option("/name"));
+        assert!(looks_like_option("/path=./x"));
+        assert!(!looks_like_option("/tmp/x"));
+        assert!(!looks_like_option("word"));
+    }
+
+    #[test]
+    #[should_panic(expected = "parameter message is already the trailing text parameter")]
+    fn second_trailing_text_parameter_panics() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_simple_parameter("message", ParameterType::TrailingText, "message");
+        processor.add_simple_parameter("rest", ParameterType::TrailingText, "rest");
+    }
+
+    #[test]
+    fn try_parse_starts_from_defaults_each_time() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_can_empty_parameter("a", ParameterType::Flag, ParameterValue::None, "a");
+        processor.add_can_empty_parameter("b", ParameterType::Flag, ParameterValue::None, "b");
+        let name = processor.add_can_empty_parameter(
+            "name",
+            ParameterType::String,
+            ParameterValue::None,
+            "name",
+        );
+        processor.add_order_constraint("a", "b");
+
+        assert!(processor
+            .try_parse(&args(&["--b", "--a", "--name", "x"]))
+            .is_err());
+        assert_eq!(
+            processor.try_parse(&args(&["--a", "--b"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert!(name.get_value().is_none());
+
+        assert_eq!(processor.try_parse(&args(&[])), Ok(ParseOutcome::Parsed));
+        assert!(!processor.is_parameter_set("a"));
+        assert_eq!(processor.get_parameter("b").unwrap().occurrences(), 0);
+    }
+
+    #[test]
+    fn repeated_parses_do_not_stack_list_layers() {
+        let mut processor = CommandLineProcessor::new();
+        let default = ParameterValue::List(vec!["base".to_string()]);
+        let tags = processor.add_can_empty_parameter("tags", ParameterType::List, default, "tags");
+        tags.merge_strategy(MergeStrategy::Append);
+
+        processor.parse_partial(&args(&["--tags", "a"]));
+        assert_eq!(
+            processor.try_parse(&args(&["--tags", "b"])),
+            Ok(ParseOutcome::Parsed)
+        );
+
+        assert_eq!(tags.to_list_value().unwrap(), vec!["base", "b"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "relation uses unknown parameter mx")]
+    fn relation_with_unknown_parameter_panics() {
+        let mut processor = relation_processor(Comparison::LessOrEqual);
+        processor.add_relation(Relation::new("min", Comparison::LessOrEqual, "mx"));
+    }
+
+    #[test]
+    fn implied_flag_satisfies_group() {
+        let mut processor = implication_processor("RCA_IMPLIED_GROUP");
+        processor.add_at_least_one_group(&["no-progress"]);
+
+        assert_eq!(
+            processor.try_parse(&args(&["--quiet"])),
+            Ok(ParseOutcome::Parsed)
+        );
+        assert!(processor.try_parse(&args(&[])).is_err());
+    }
+
+    #[test]
+    fn implied_experimental_flag_requires_gate() {
+        let mut processor = experimental_processor();
+        processor.add_can_empty_parameter(
+            "quiet",
+            ParameterType::Flag,
+            ParameterValue::None,
+            "quiet",
+        );
+        processor.add_implication("quiet", &["turbo"]);
+
+        let errors = processor.try_parse(&args(&["--quiet"])).unwrap_err();
+        assert_eq!(errors[0].kind, ParseErrorKind::Constraint);
+        assert_eq!(errors[0].parameter.as_deref(), Some("turbo"));
+
+        assert_eq!(
+            processor.try_parse(&args(&["--quiet", "--unstable"])),
+            Ok(ParseOutcome::Parsed)
+        );
+    }
+
+    #[test]
+    fn implied_flag_is_exported() {
+        let mut processor = implication_processor("RCA_IMPLIED_EXPORT");
+
+        assert_eq!(
+            processor.try_parse(&args(&["--quiet"])),
+            Ok(ParseOutcome::Parsed)
+        );
+
+        assert_eq!(
+            processor.export_env("APP"),
+            "export APP_NO_COLOR='true'\nexport APP_NO_PROGRESS='true'\nexport APP_QUIET='true'\n"
+        );
     }
 }
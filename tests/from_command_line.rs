@@ -0,0 +1,90 @@
+//! Runs `examples/copy.rs`, which builds its options with `rust_cmd_arg::parse`, as a child
+//! process so the real command line handling and exit codes are exercised.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::Once;
+
+/// Builds the example once and returns the path of its binary.
+fn example_binary() -> PathBuf {
+    static BUILD: Once = Once::new();
+    BUILD.call_once(|| {
+        let mut cargo = Command::new(env!("CARGO"));
+        cargo
+            .args(["build", "--quiet", "--example", "copy", "--manifest-path"])
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+        if !cfg!(debug_assertions) {
+            cargo.arg("--release");
+        }
+        let status = cargo.status().expect("failed to run cargo");
+        assert!(status.success(), "failed to build the copy example");
+    });
+
+    // the test binary lives in target/<profile>/deps, examples in target/<profile>/examples
+    let mut path = std::env::current_exe().unwrap();
+    path.pop();
+    path.pop();
+    path.push("examples");
+    path.push(format!("copy{}", std::env::consts::EXE_SUFFIX));
+    path
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(example_binary())
+        .args(args)
+        .output()
+        .expect("failed to run the copy example")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn registered_type_is_built_from_arguments() {
+    let output = run(&["-s", "notes.txt", "--jobs", "4", "--dry-run"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "source=notes.txt jobs=4 dry_run=true\n");
+}
+
+#[test]
+fn defaults_fill_omitted_fields() {
+    let output = run(&["--source=notes.txt"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "source=notes.txt jobs=1 dry_run=false\n");
+}
+
+#[test]
+fn missing_required_parameter_exits_before_extraction() {
+    let output = run(&["--jobs", "2"]);
+
+    assert_ne!(output.status.code(), Some(0));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).starts_with("error: "));
+    assert!(stderr(&output).contains("source"));
+}
+
+#[test]
+fn from_processor_error_is_returned() {
+    let output = run(&["-s", "notes.txt", "--jobs", "0"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stderr(&output), "error: jobs: must be at least 1\n");
+}
+
+#[test]
+fn default_help_and_version_aliases_are_registered() {
+    let help = run(&["-h"]);
+    assert!(stderr(&help).contains("--dry-run"));
+    assert!(stdout(&help).is_empty());
+
+    let version = run(&["-V"]);
+    assert_eq!(version.status.code(), Some(0));
+    assert_eq!(stdout(&version), "copy 1.0\n");
+}